@@ -0,0 +1,63 @@
+/*!
+Training throughput, in transitions per second, for an increasing amount of threads.
+
+Run with `cargo bench --bench throughput`, or `cargo bench --bench throughput -- <iterations>`.
+*/
+
+use std::time::Instant;
+use whister::{
+    fortify::{Policy, QLearner, Schedule},
+    game::Game,
+    gamestate::GameState,
+};
+
+fn transitions_per_second(threads: usize, iterations: u64, policy: Policy) -> f64 {
+    let mut learner: QLearner<GameState> = QLearner::new_with_iter(iterations);
+    learner.enable_self_play();
+    learner.policy = policy;
+    learner.set_threads(threads);
+    learner.set_seed(0);
+    learner.disable_verbose();
+
+    let start = Instant::now();
+    learner.train(&mut Game::new());
+
+    iterations as f64 / start.elapsed().as_secs_f64()
+}
+
+fn main() {
+    // cargo bench passes `--bench` along
+    let iterations = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(1000000);
+
+    // 1, 2, 4, ... up to all cpus
+    let cpus = num_cpus::get();
+    let mut counts: Vec<usize> = (0..)
+        .map(|k| 1 << k)
+        .take_while(|&threads| threads < cpus.max(2))
+        .collect();
+    counts.push(cpus.max(2));
+
+    let policies = [
+        ("random", Policy::Random),
+        (
+            "epsilon-greedy",
+            Policy::EpsilonGreedy {
+                epsilon: 0.3,
+                schedule: Schedule::Constant,
+            },
+        ),
+    ];
+
+    println!("{iterations} iterations per training");
+    println!("{:>16} {:>8} {:>16}", "policy", "threads", "transitions/s");
+
+    for (name, policy) in policies {
+        for &threads in &counts {
+            let speed = transitions_per_second(threads, iterations, policy);
+            println!("{name:>16} {threads:>8} {speed:>16.0}");
+        }
+    }
+}
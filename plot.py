@@ -1,15 +1,80 @@
+"""
+Plot the results of the tune binary as heatmaps:        python plot.py [out.csv]
+or the metrics of one or more trainings as learning curves: python plot.py metrics.csv other.jsonl
+"""
+
+import sys
+
 import pandas as pd
 import seaborn as sns
 import matplotlib.pyplot as plt
 
-data = pd.read_csv('out.csv')
 
-grid_data = data.pivot(index='discount', columns='learning_rate', values='score')
+def read(path):
+    if path.endswith('.jsonl'):
+        return pd.read_json(path, lines=True)
+    return pd.read_csv(path)
+
+
+def plot_tuning(data):
+    # successive halving trains the best trials longer, only show the longest trainings
+    if 'iterations' in data.columns:
+        data = data[data['iterations'] == data['iterations'].max()]
+
+    # older tuning results don't have an algorithm column
+    if 'algorithm' not in data.columns:
+        data['algorithm'] = 'q-learning'
+
+    algorithms = data['algorithm'].unique()
+
+    fig, axes = plt.subplots(1, len(algorithms), figsize=(10 * len(algorithms), 6), squeeze=False)
+
+    for ax, algorithm in zip(axes[0], algorithms):
+        subset = data[data['algorithm'] == algorithm]
+        # average over the other hyperparameters that were searched
+        grid_data = subset.pivot_table(index='discount', columns='learning_rate', values='score', aggfunc='mean')
+
+        sns.heatmap(grid_data, cmap='viridis', annot=True, fmt=".4f", cbar_kws={'label': 'Score'}, ax=ax)
+        ax.set_title(f'2D Grid of Scores ({algorithm})')
+        ax.set_xlabel('Learning Rate')
+        ax.set_ylabel('Discount')
 
-plt.figure(figsize=(10, 6))  # Adjust the figure size as needed
-sns.heatmap(grid_data, cmap='viridis', annot=True, fmt=".4f", cbar_kws={'label': 'Score'})
-plt.title('2D Grid of Scores')
-plt.xlabel('Learning Rate')
-plt.ylabel('Discount')
-plt.show()
 
+def plot_learning_curves(runs):
+    curves = [
+        ('score', 'Tricks per deal (fixed deals)'),
+        ('td_error', 'Mean absolute TD error'),
+        ('q_mean', 'Mean Q value'),
+        ('q_std', 'Std of Q values'),
+        ('states', 'States discovered'),
+        ('exploration', 'Exploration'),
+    ]
+
+    fig, axes = plt.subplots(2, 3, figsize=(18, 9))
+
+    for ax, (column, title) in zip(axes.flat, curves):
+        for name, data in runs:
+            if column in data.columns and data[column].notna().any():
+                ax.plot(data['iteration'], data[column], label=name)
+
+        # a diverging training shows up as exploding extremes
+        if column == 'q_mean':
+            for name, data in runs:
+                ax.fill_between(data['iteration'], data['q_min'], data['q_max'], alpha=0.15)
+
+        ax.set_title(title)
+        ax.set_xlabel('Iteration')
+
+    axes.flat[0].legend()
+    fig.tight_layout()
+
+
+paths = sys.argv[1:] or ['out.csv']
+runs = [(path, read(path)) for path in paths]
+
+if 'iteration' in runs[0][1].columns:
+    plot_learning_curves(runs)
+else:
+    plot_tuning(runs[0][1])
+
+plt.show()
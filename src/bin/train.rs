@@ -1,65 +1,625 @@
+/*!
+Train a whister AI model.
+
+Every setting can be given on the command line, see `train --help`.
+Settings that are missing are asked for interactively, unless `--no-input` is given
+or there is no terminal to ask them on, in which case the defaults are used.
+*/
+
+use clap::{parser::ValueSource, CommandFactory, FromArgMatches, Parser, ValueEnum};
+use std::io::{stdin, IsTerminal};
+use std::time::Duration;
 use text_io::read;
 use whister::{
-    fortify::{self, QLearner},
+    fortify::{
+        self, data::Selection, Algorithm, ModelHeader, Policy, QLearner, RateDecay, Replay,
+        Sampling, Schedule, ScheduleKind, TableSharing, Target,
+    },
     game::Game,
     gamestate::GameState,
-    show,
+    render, show,
 };
 
-fn main() {
-    let mut learner: QLearner<GameState>;
-    let mut game: Game = Game::new();
+#[derive(Parser, Debug)]
+#[command(about = "Train a whister AI model", version)]
+struct Args {
+    /// existing model to continue training from: a model file, a saved model, or easy, medium or hard
+    #[arg(short, long, conflicts_with = "new")]
+    model: Option<String>,
+
+    /// start from an empty model
+    #[arg(long)]
+    new: bool,
+
+    /// resume the training checkpoint with this name, a multi-agent training needs --multi-agent again
+    ///
+    /// The training goes on with the settings of the checkpoint, which can't be changed.
+    #[arg(long, conflicts_with_all = ["model", "new"], conflicts_with_all = TRAINING_SETTINGS)]
+    resume: Option<String>,
+
+    /// amount of iterations to train (in total, when resuming)
+    #[arg(short, long)]
+    iterations: Option<u64>,
+
+    /// let the opponents use the model that is being trained
+    #[arg(long, conflicts_with = "no_self_play")]
+    self_play: bool,
+
+    /// let the opponents use the rule based AI
+    #[arg(long)]
+    no_self_play: bool,
+
+    /// learning rate
+    #[arg(long)]
+    rate: Option<f64>,
+
+    /// how the learning rate changes over the training
+    #[arg(long, value_enum, default_value_t = ScheduleArg::Constant)]
+    rate_schedule: ScheduleArg,
+
+    /// learning rate at the end of a scheduled training [default: a twentieth of the rate]
+    #[arg(long)]
+    rate_end: Option<f64>,
+
+    /// decay the learning rate of a state-action pair to rate / visits^exponent
+    #[arg(long, default_value_t = 0.0)]
+    rate_decay: f64,
+
+    /// discount of future rewards
+    #[arg(long)]
+    discount: Option<f64>,
+
+    /// how the discount changes over the training
+    #[arg(long, value_enum, default_value_t = ScheduleArg::Constant)]
+    discount_schedule: ScheduleArg,
+
+    /// discount at the end of a scheduled training [default: a twentieth of the discount]
+    #[arg(long)]
+    discount_end: Option<f64>,
+
+    /// amount of transitions per worker that are gathered before updating
+    #[arg(long)]
+    queue_size: Option<usize>,
+
+    /// seed of the learner, for reproducible exploration
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// exploration policy
+    #[arg(long, value_enum)]
+    policy: Option<PolicyArg>,
+
+    /// initial epsilon (epsilon-greedy) or temperature (softmax)
+    #[arg(long, default_value_t = 0.3)]
+    exploration: f64,
+
+    /// what the values are learned from
+    #[arg(long, value_enum)]
+    target: Option<TargetArg>,
+
+    /// amount of steps for the n-step target [default: 3]
+    #[arg(long)]
+    steps: Option<usize>,
+
+    /// the temporal difference algorithm
+    #[arg(long, value_enum)]
+    algorithm: Option<AlgorithmArg>,
+
+    /// learn from a replay buffer holding this many transitions
+    #[arg(long)]
+    replay: Option<usize>,
+
+    /// sample the replay buffer prioritized by temporal difference error
+    #[arg(long, requires = "replay")]
+    prioritized: bool,
+
+    /// amount of minibatches replayed for every batch of new transitions
+    #[arg(long, default_value_t = 1, requires = "replay")]
+    replay_ratio: usize,
+
+    /// let all four players learn at the same time, with a shared table or one each
+    #[arg(long, value_enum, conflicts_with = "self_play")]
+    multi_agent: Option<SharingArg>,
+
+    /// with --multi-agent: players across from each other are partners
+    #[arg(long, requires = "multi_agent")]
+    teams: bool,
+
+    /// write checkpoints with this name while training
+    #[arg(long)]
+    checkpoint: Option<String>,
+
+    /// write a checkpoint every this many iterations (default: a tenth of the training)
+    #[arg(long)]
+    checkpoint_every: Option<u64>,
+
+    /// export the visit counts of the trained model to this csv file
+    #[arg(long)]
+    export_visits: Option<String>,
+
+    /// write training metrics to this csv (or .jsonl) file, to plot learning curves
+    #[arg(long)]
+    metrics: Option<String>,
+
+    /// measure the training every this many iterations (default: a hundredth of the training)
+    #[arg(long)]
+    metrics_every: Option<u64>,
+
+    /// amount of fixed deals the model is evaluated on at every measurement
+    #[arg(long, default_value_t = 500)]
+    metrics_deals: usize,
+
+    /// amount of deals to evaluate the trained model on, against rule based opponents
+    #[arg(long)]
+    eval_deals: Option<usize>,
+
+    /// save the trained model with this name in the data directory, or as this model file
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// save only the best action of every state, which makes the model a lot smaller
+    #[arg(long)]
+    reduced: bool,
+
+    /// never ask anything, use the defaults for missing settings
+    #[arg(long)]
+    no_input: bool,
+
+    /// were any of the `TRAINING_SETTINGS` given on the command line?
+    #[arg(skip)]
+    settings_given: bool,
+}
+
+/// the settings of a new training, a resumed training keeps those of its checkpoint
+const TRAINING_SETTINGS: [&str; 18] = [
+    "self_play",
+    "no_self_play",
+    "rate",
+    "rate_schedule",
+    "rate_end",
+    "rate_decay",
+    "discount",
+    "discount_schedule",
+    "discount_end",
+    "queue_size",
+    "seed",
+    "policy",
+    "exploration",
+    "target",
+    "steps",
+    "algorithm",
+    "replay",
+    "checkpoint",
+];
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum PolicyArg {
+    Random,
+    Epsilon,
+    Softmax,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ScheduleArg {
+    Constant,
+    Exponential,
+    Linear,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum TargetArg {
+    OneStep,
+    NStep,
+    MonteCarlo,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum AlgorithmArg {
+    QLearning,
+    DoubleQLearning,
+    Sarsa,
+    ExpectedSarsa,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum SharingArg {
+    Shared,
+    Separate,
+}
+
+/// default amount of evaluation deals, about the amount of tricks in the original evaluation
+const EVAL_DEALS: usize = 3077;
+
+/// seed of the deals the metrics are evaluated on, the same for every training
+const METRICS_SEED: u64 = 3077;
+
+impl Args {
+    /// should missing settings be asked for?
+    fn interactive(&self) -> bool {
+        !self.no_input && stdin().is_terminal()
+    }
+}
+
+/// ask a question, `None` when nothing (or no) was answered
+fn ask(question: &str) -> Option<String> {
+    print!("{question}{}", render::ansi(render::BOLD));
+    let answer = show::get_answer();
+    print!("{}", render::ansi(render::RESET));
+
+    answer.filter(|a| !a.is_empty() && a != "N" && a != "n")
+}
 
-    let q = fortify::data::select_model(true);
+fn policy_from(arg: PolicyArg, exploration: f64, iterations: u64) -> Policy {
+    // explore less and less, until a twentieth of the start is reached
+    let schedule = Schedule::annealing(exploration, iterations);
 
-    print!("How many iterations? \x1b[1m");
-    let iterations: u64 = read!();
-    print!("\x1b[0m");
-    learner = QLearner::new_with_iter(iterations);
+    match arg {
+        PolicyArg::Random => Policy::Random,
+        PolicyArg::Epsilon => Policy::EpsilonGreedy {
+            epsilon: exploration,
+            schedule,
+        },
+        PolicyArg::Softmax => Policy::Softmax {
+            temperature: exploration,
+            schedule,
+        },
+    }
+}
+
+/// a schedule of `arg` from `start` to `end`, a twentieth of `start` if not given
+fn schedule_from(arg: ScheduleArg, start: f64, end: Option<f64>, iterations: u64) -> Schedule {
+    let kind = match arg {
+        ScheduleArg::Constant => ScheduleKind::Constant,
+        ScheduleArg::Exponential => ScheduleKind::Exponential,
+        ScheduleArg::Linear => ScheduleKind::Linear,
+    };
+
+    let end = end.unwrap_or(start / 20.0);
+    Schedule::spanning(kind, start, end, iterations).unwrap_or_else(|err| {
+        eprintln!("Invalid schedule: {err}");
+        std::process::exit(1);
+    })
+}
+
+/// resume the checkpoint given on the command line, or ask whether to resume one
+fn resume(args: &Args) -> Option<QLearner<GameState>> {
+    let name = match &args.resume {
+        Some(name) => name.clone(),
+        None => {
+            // without a data directory, there is nothing to resume
+            let checkpoints = fortify::data::list_checkpoints().unwrap_or_default();
+            if !args.interactive()
+                || checkpoints.is_empty()
+                || args.model.is_some()
+                || args.new
+                || args.settings_given
+            {
+                return None;
+            }
+
+            println!("Available checkpoints: {}", checkpoints.join(", "));
+            let answer = ask("Resume a checkpoint? [name/N]: ")?;
+
+            if !checkpoints.contains(&answer) {
+                return None;
+            }
+            answer
+        }
+    };
+
+    let mut learner = QLearner::resume(&name).unwrap_or_else(|err| {
+        eprintln!("Could not read checkpoint {name}: {err}");
+        std::process::exit(1);
+    });
+
+    // without more iterations, the original training is just finished
+    match args.iterations {
+        Some(iterations) => learner.set_iterations(iterations),
+        None if args.interactive() => {
+            print!(
+                "Train until how many iterations? (done: {}) {}",
+                learner.current_iteration(),
+                render::ansi(render::BOLD)
+            );
+            let iterations: u64 = read!();
+            print!("{}", render::ansi(render::RESET));
+            learner.set_iterations(iterations);
+        }
+        None => {}
+    }
+
+    // keep checkpointing under the same name
+    learner.enable_checkpoints(&name, args.checkpoint_every, Some(Duration::from_secs(600)));
+
+    Some(learner)
+}
+
+/// set up a new training from the command line, asking for what's missing
+fn new_learner(args: &Args) -> QLearner<GameState> {
+    let interactive = args.interactive();
+
+    let q = match &args.model {
+        Some(model) => {
+            let q = fortify::data::bin_to_q(model).unwrap_or_else(|err| {
+                eprintln!("Could not read model {model}: {err}");
+                std::process::exit(1);
+            });
+            Some(q)
+        }
+        None if args.new || !interactive => None,
+        None => match fortify::data::select_model(true) {
+            Ok(Selection::Model(q)) => Some(q),
+            Ok(Selection::New) => None,
+            Ok(Selection::Cancel) => std::process::exit(0),
+            Err(err) => {
+                eprintln!("Could not select a model: {err}");
+                std::process::exit(1);
+            }
+        },
+    };
+
+    let iterations = match args.iterations {
+        Some(iterations) => iterations,
+        None if interactive => {
+            print!("How many iterations? {}", render::ansi(render::BOLD));
+            let iterations: u64 = read!();
+            print!("{}", render::ansi(render::RESET));
+            iterations
+        }
+        None => 1000000,
+    };
+
+    let mut learner = QLearner::new_with_iter(iterations);
 
     if let Some(q) = q {
         learner.set_q(q);
     }
 
+    if let Some(seed) = args.seed {
+        learner.set_seed(seed);
+    }
+    if let Some(rate) = args.rate {
+        learner.rate = rate;
+    }
+    if let Some(discount) = args.discount {
+        learner.discount = discount;
+    }
+    if let Some(queue_size) = args.queue_size {
+        learner.queue_size = queue_size;
+    }
+
+    learner.rate_schedule =
+        schedule_from(args.rate_schedule, learner.rate, args.rate_end, iterations);
+    learner.rate_decay = RateDecay::with_exponent(args.rate_decay);
+    learner.discount_schedule = schedule_from(
+        args.discount_schedule,
+        learner.discount,
+        args.discount_end,
+        iterations,
+    );
+    if learner.rate_schedule != Schedule::Constant {
+        println!("-> learning rate schedule: {}", learner.rate_schedule);
+    }
+    if learner.rate_decay != RateDecay::Constant {
+        println!(
+            "-> learning rate decays with the visits as {}",
+            learner.rate_decay
+        );
+    }
+    if learner.discount_schedule != Schedule::Constant {
+        println!("-> discount schedule: {}", learner.discount_schedule);
+    }
+
     // extend training based on an existing model or start over
-    print!("Enable self-play? [Y/n]: \x1b[1m");
-    let answer = show::yes_or_no(true);
-    print!("\x1b[0m");
-    if answer {
+    let self_play = if args.self_play || args.no_self_play {
+        args.self_play
+    } else if interactive {
+        print!("Enable self-play? [Y/n]: {}", render::ansi(render::BOLD));
+        let answer = show::yes_or_no(true);
+        print!("{}", render::ansi(render::RESET));
+        answer
+    } else {
+        true
+    };
+    if self_play {
         learner.enable_self_play();
         println!("-> self play is enabled");
     }
 
-    learner.train(&mut Game::new());
+    let policy = match args.policy {
+        Some(policy) => policy,
+        None if interactive => {
+            match ask("Exploration policy? [R]andom / [e]psilon-greedy / [s]oftmax: ").as_deref() {
+                Some("e" | "E") => PolicyArg::Epsilon,
+                Some("s" | "S") => PolicyArg::Softmax,
+                _ => PolicyArg::Random,
+            }
+        }
+        None => PolicyArg::Random,
+    };
+    learner.policy = policy_from(policy, args.exploration, iterations);
+    println!("-> exploring with a {} policy", learner.policy);
+
+    let target = match args.target {
+        Some(target) => target,
+        None if interactive => {
+            match ask("Learning target? [1]-step / [n]-step / [m]onte carlo: ").as_deref() {
+                Some("n" | "N") => TargetArg::NStep,
+                Some("m" | "M") => TargetArg::MonteCarlo,
+                _ => TargetArg::OneStep,
+            }
+        }
+        None => TargetArg::OneStep,
+    };
+    learner.target = match target {
+        TargetArg::OneStep => Target::OneStep,
+        TargetArg::NStep => match args.steps {
+            Some(steps) => Target::NStep(steps),
+            None if interactive => {
+                print!("How many steps? {}", render::ansi(render::BOLD));
+                let steps: usize = read!();
+                print!("{}", render::ansi(render::RESET));
+                Target::NStep(steps)
+            }
+            None => Target::NStep(3),
+        },
+        TargetArg::MonteCarlo => Target::MonteCarlo,
+    };
+    println!("-> learning from {} returns", learner.target);
+
+    let algorithm = match args.algorithm {
+        Some(algorithm) => algorithm,
+        None if interactive => match ask(
+            "Algorithm? [Q]-learning / [d]ouble Q-learning / [s]arsa / [e]xpected sarsa: ",
+        )
+        .as_deref()
+        {
+            Some("d" | "D") => AlgorithmArg::DoubleQLearning,
+            Some("s" | "S") => AlgorithmArg::Sarsa,
+            Some("e" | "E") => AlgorithmArg::ExpectedSarsa,
+            _ => AlgorithmArg::QLearning,
+        },
+        None => AlgorithmArg::QLearning,
+    };
+    learner.algorithm = match algorithm {
+        AlgorithmArg::QLearning => Algorithm::QLearning,
+        AlgorithmArg::DoubleQLearning => Algorithm::DoubleQLearning,
+        AlgorithmArg::Sarsa => Algorithm::Sarsa,
+        AlgorithmArg::ExpectedSarsa => Algorithm::ExpectedSarsa,
+    };
+    println!("-> training with {}", learner.algorithm);
+
+    if let Some(capacity) = args.replay {
+        let sampling = match args.prioritized {
+            true => Sampling::Prioritized {
+                alpha: 0.6,
+                beta: 0.4,
+            },
+            false => Sampling::Uniform,
+        };
+        learner.replay = Some(Replay {
+            capacity,
+            sampling,
+            ratio: args.replay_ratio.max(1),
+        });
+        println!("-> replaying {} transitions", sampling);
+    }
+
+    let checkpoint = match &args.checkpoint {
+        Some(name) => Some(name.clone()),
+        None if interactive => ask("Write checkpoints while training? [checkpointname/N]: "),
+        None => None,
+    };
+    if let Some(name) = checkpoint {
+        // every tenth of the training by default, and at least every ten minutes
+        let every = args.checkpoint_every.unwrap_or((iterations / 10).max(1));
+        learner.enable_checkpoints(&name, Some(every), Some(Duration::from_secs(600)));
+        println!("-> checkpoints are written as {name}");
+    }
+
+    learner
+}
+
+fn main() {
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    args.settings_given = TRAINING_SETTINGS
+        .iter()
+        .any(|id| matches.value_source(id) == Some(ValueSource::CommandLine));
+    let interactive = args.interactive();
+
+    let mut learner = match resume(&args) {
+        Some(learner) => learner,
+        None => new_learner(&args),
+    };
+
+    if let Some(path) = &args.metrics {
+        let every = args
+            .metrics_every
+            .unwrap_or((learner.iterations() / 100).max(1));
+        let deals = args.metrics_deals;
+        let evaluate = move |q: &_| Game::evaluate_seeded(q, deals, METRICS_SEED);
+
+        learner
+            .enable_metrics(path, every, Some(Box::new(evaluate)))
+            .unwrap_or_else(|e| {
+                eprintln!("Could not open metrics file {path}: {e}");
+                std::process::exit(1);
+            });
+        println!("-> metrics are written to {path}");
+    }
+
+    match args.multi_agent {
+        Some(sharing) => {
+            let sharing = match sharing {
+                SharingArg::Shared => TableSharing::Shared,
+                SharingArg::Separate => TableSharing::Separate,
+            };
+
+            let mut game = Game::new();
+            if args.teams {
+                game.set_teams([0, 1, 0, 1]);
+            }
+            learner.train_multi_agent(&game, sharing);
+        }
+        None => learner.train(&mut Game::new()),
+    }
 
     let q = learner.get_q();
 
-    loop {
-        for _ in 0..40001 {
-            let best_card_id = game.best_card_id_ai(&q);
+    let rare = fortify::under_trained(&learner.get_visits(), 10).len();
+    println!("{rare} state-action pairs have been visited less than 10 times");
 
-            // play rounds against a rule based opponent
-            game.agent_plays_round(best_card_id, &None);
+    let export = match &args.export_visits {
+        Some(path) => Some(path.clone()),
+        None if interactive => ask("Export visit counts? [file.csv/N]: "),
+        None => None,
+    };
+    if let Some(path) = export {
+        // the model is still worth saving when the export fails
+        if let Err(err) = fortify::data::visits_to_csv(&learner.get_visits(), &path) {
+            eprintln!("Could not export the visit counts to {path}: {err}");
         }
+    }
 
-        game.show_scores();
+    let deals = args.eval_deals.unwrap_or(EVAL_DEALS);
+    let mut score;
+    loop {
+        // play deals against a rule based opponent
+        score = Game::evaluate(&q, deals);
+        println!(
+            "Average tricks per deal over {deals} deals: {}",
+            render::bold(format!("{score:.4}"))
+        );
 
-        print!("Play another round? [y/N]: \x1b[1m");
+        if !interactive || args.eval_deals.is_some() {
+            break;
+        }
+
+        print!("Play another round? [y/N]: {}", render::ansi(render::BOLD));
         let answer = show::yes_or_no(false);
-        print!("\x1b[0m");
+        print!("{}", render::ansi(render::RESET));
         if !answer {
             break;
         }
     }
 
-    print!("Save this model? [modelname/N]: \x1b[1m");
-    let answer = show::get_answer();
-    print!("\x1b[0m");
-    if let Some(answer) = answer {
-        if answer == "N" || answer == "n" || answer.is_empty() {
-            return;
+    let output = match &args.output {
+        Some(name) => Some(name.clone()),
+        None if interactive => ask("Save this model? [modelname/N]: "),
+        None => {
+            println!("The model is not saved, use --output to save it.");
+            None
+        }
+    };
+    if let Some(answer) = output {
+        println!("Saving model as {}", render::italic(format!("{answer:?}")));
+        let header = ModelHeader::new::<GameState>(args.reduced)
+            .with_hyperparameters(learner.hyperparameters())
+            .with_score(score);
+        if let Err(err) = fortify::data::save_model(&q, &answer, &header) {
+            eprintln!("Could not save the model: {err}");
+            std::process::exit(1);
         }
-        println!("Saving model as \x1b[3m{:?}\x1b[0m", answer);
-        fortify::data::q_to_bin(&q, answer, false).expect("Should be able to save");
     }
 }
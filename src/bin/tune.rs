@@ -1,53 +1,86 @@
-use whister::{fortify::QLearner, game::Game, gamestate::GameState};
+/*!
+Search for good hyperparameters of a whister AI model.
 
-fn main() {
-    let mut learner: QLearner<GameState>;
-    let mut discounts = Vec::new();
-
-    for i in 1..10 {
-        discounts.push(i as f64 / 10.0);
-    }
+The search is described by a toml config file, see `fortify::tune` for its settings.
+Without a config file, the original grid of learning rates and discounts is searched.
+*/
 
-    let mut lrs = Vec::new();
-    for i in 1..10 {
-        lrs.push(i as f64 / 50.0);
-    }
+use clap::Parser;
+use whister::{
+    fortify::tune::{self, StateType, TuneConfig},
+    game::Game,
+    gamestate::GameState,
+    render,
+};
 
-    let mut wtr = csv::Writer::from_path("out.csv").unwrap();
-    wtr.write_record(&["discount", "learning_rate", "score"])
-        .unwrap();
+#[derive(Parser, Debug)]
+#[command(
+    about = "Search for good hyperparameters of a whister AI model",
+    version
+)]
+struct Args {
+    /// toml file describing the search
+    config: Option<String>,
 
-    for discount in &discounts {
-        for lr in &lrs {
-            let mut game: Game = Game::new();
+    /// csv or json file to write the results to, instead of the one in the config
+    #[arg(short, long)]
+    output: Option<String>,
 
-            learner = QLearner::new_with_iter(1000000);
-
-            learner.enable_self_play();
-            learner.rate = *lr;
-            learner.discount = *discount;
-
-            learner.train(&mut Game::new());
+    /// amount of trainings that run at the same time, instead of the one in the config
+    #[arg(short, long)]
+    parallel: Option<usize>,
+}
 
-            let q = learner.get_q();
+fn main() {
+    let args = Args::parse();
 
-            for _ in 0..40001 {
-                let best_card_id = game.best_card_id_ai(&q);
+    let mut config = match &args.config {
+        Some(path) => TuneConfig::from_file(path).unwrap_or_else(|e| {
+            eprintln!("Could not read config: {e}");
+            std::process::exit(1);
+        }),
+        None => TuneConfig::default(),
+    };
 
-                // play rounds against a rule based opponent
-                game.agent_plays_round(best_card_id, &None);
-            }
+    if let Some(output) = args.output {
+        config.output = output;
+    }
+    if let Some(parallel) = args.parallel {
+        config.parallel = parallel.max(1);
+    }
 
-            let score = game.get_ai_score() as f32 / (10000.0);
+    let evaluate = |trial: &tune::Trial, run: &tune::Run| match trial.state {
+        StateType::Game => {
+            let mut learner = config.learner::<GameState>(trial, run);
+            learner.train(&mut Game::new());
 
-            wtr.write_record(&[
-                format!("{}", discount).as_str(),
-                format!("{}", lr).as_str(),
-                format!("{}", score).as_str(),
-            ])
-            .unwrap();
+            // play deals against a rule based opponent
+            Game::evaluate(&learner.get_q(), config.eval_deals)
         }
+    };
+    let progress = |run: tune::Progress| {
+        println!(
+            "[{}/{}] {} ({} iterations): {:.4}",
+            run.done, run.runs, run.trial, run.iterations, run.score
+        );
+    };
+    let results = config.search(evaluate, progress);
+
+    if let Some(best) = results
+        .iter()
+        .filter(|r| r.iterations == config.iterations)
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+    {
+        println!(
+            "Best: {} with {} tricks per deal",
+            best.trial,
+            render::bold(format!("{:.4} ± {:.4}", best.score, best.score_std))
+        );
     }
 
-    wtr.flush().unwrap();
+    tune::write_results(&results, &config.output).unwrap_or_else(|e| {
+        eprintln!("Could not write results: {e}");
+        std::process::exit(1);
+    });
+    println!("Results are written to {}", config.output);
 }
@@ -0,0 +1,329 @@
+/*!
+Inspect, compare and convert whister AI models.
+
+Every model argument can be a model file, the name of a saved model,
+or one of the built-in models: easy, medium or hard.
+*/
+
+use clap::{Parser, Subcommand};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use whister::{
+    fortify::{self, data, Metrics, ModelHeader, Q},
+    gamestate::{Action, GameState},
+    render,
+};
+
+#[derive(Parser, Debug)]
+#[command(about = "Inspect, compare and convert whister AI models", version)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// show the header of a model and statistics of its table
+    Stats { model: String },
+    /// print the best action in every state
+    Dump {
+        model: String,
+
+        /// print every action with its value, instead of only the best one
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// show the states in which two models choose a different action
+    Diff {
+        first: String,
+        second: String,
+
+        /// show at most this many states
+        #[arg(short, long)]
+        limit: Option<usize>,
+    },
+    /// drop the states that were rarely visited during training
+    ///
+    /// Models don't store how often their states were visited, so this needs
+    /// a checkpoint of the training that produced the model (see `train --checkpoint`).
+    Prune {
+        model: String,
+
+        /// checkpoint of the training of the model, models have no visit counts of their own
+        #[arg(short, long)]
+        visits: String,
+
+        /// keep the states that were visited at least this many times
+        #[arg(short, long, default_value_t = 10)]
+        min_visits: u64,
+
+        /// where to save the pruned model, a model file or a name in the data directory
+        #[arg(short, long)]
+        output: String,
+    },
+    /// save a model again in the current format, optionally reduced or full
+    Convert {
+        model: String,
+
+        /// only keep the best action of every state
+        #[arg(long, conflicts_with = "full")]
+        reduced: bool,
+
+        /// keep every action value (a reduced model only has values for the best actions)
+        #[arg(long)]
+        full: bool,
+
+        /// where to save the converted model, a model file or a name in the data directory
+        #[arg(short, long)]
+        output: String,
+    },
+}
+
+/// amount of bars in the value histogram
+const BINS: usize = 10;
+
+fn main() {
+    let args = Args::parse();
+
+    let result = match args.command {
+        Command::Stats { model } => stats(&model),
+        Command::Dump { model, all } => dump(&model, all),
+        Command::Diff {
+            first,
+            second,
+            limit,
+        } => diff(&first, &second, limit),
+        Command::Prune {
+            model,
+            visits,
+            min_visits,
+            output,
+        } => prune(&model, &visits, min_visits, &output),
+        Command::Convert {
+            model,
+            reduced,
+            full,
+            output,
+        } => {
+            let reduced = match (reduced, full) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            };
+            convert(&model, reduced, &output)
+        }
+    };
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn load(model: &str) -> Result<(ModelHeader, Q<GameState>), String> {
+    data::bin_to_model(model).map_err(|e| format!("Could not read model {model}: {e}"))
+}
+
+fn save(q: &Q<GameState>, output: &str, header: &ModelHeader) -> Result<(), String> {
+    data::save_model(q, output, header).map_err(|e| format!("Could not save {output}: {e}"))
+}
+
+fn stats(model: &str) -> Result<(), String> {
+    let (header, q) = load(model)?;
+
+    println!("{}", render::bold(model));
+    print!("{header}");
+    println!();
+
+    let metrics = Metrics::of_table(&q);
+    println!("states: {}", metrics.states);
+    println!("state-action pairs: {}", metrics.state_actions);
+
+    let mut actions_per_state: BTreeMap<usize, usize> = BTreeMap::new();
+    for actions in q.values() {
+        *actions_per_state.entry(actions.len()).or_default() += 1;
+    }
+    println!("actions per state:");
+    for (actions, states) in &actions_per_state {
+        println!("  {actions:>2}: {states} states");
+    }
+
+    if metrics.state_actions == 0 {
+        return Ok(());
+    }
+
+    println!(
+        "values: mean {:.4}, std {:.4}, min {:.4}, max {:.4}",
+        metrics.q_mean, metrics.q_std, metrics.q_min, metrics.q_max
+    );
+
+    let mut values: Vec<f64> = q.values().flat_map(|a| a.values().copied()).collect();
+    values.sort_by(f64::total_cmp);
+    let quantile = |p: f64| values[((values.len() - 1) as f64 * p).round() as usize];
+    println!(
+        "percentiles: 5% {:.4}, 25% {:.4}, 50% {:.4}, 75% {:.4}, 95% {:.4}",
+        quantile(0.05),
+        quantile(0.25),
+        quantile(0.5),
+        quantile(0.75),
+        quantile(0.95)
+    );
+
+    // a histogram of the values, with bars relative to the largest bin
+    let width = (metrics.q_max - metrics.q_min) / BINS as f64;
+    let mut bins = [0usize; BINS];
+    for value in &values {
+        let bin = match width > 0.0 {
+            true => (((value - metrics.q_min) / width) as usize).min(BINS - 1),
+            false => 0,
+        };
+        bins[bin] += 1;
+    }
+    let largest = bins.iter().copied().max().unwrap_or(1).max(1);
+    let bar = match render::style() {
+        render::Style::Ascii => "#",
+        _ => "━",
+    };
+    for (bin, count) in bins.iter().enumerate() {
+        let from = metrics.q_min + bin as f64 * width;
+        println!(
+            "  {:>9.4} {:<40} {count}",
+            from,
+            bar.repeat(count * 40 / largest)
+        );
+    }
+
+    let mut best_actions: HashMap<Action, usize> = HashMap::new();
+    for action in data::q_to_optimal(&q).into_values() {
+        *best_actions.entry(action).or_default() += 1;
+    }
+    let mut best_actions: Vec<(Action, usize)> = best_actions.into_iter().collect();
+    best_actions.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    println!("best actions:");
+    for (action, states) in best_actions {
+        println!(
+            "  {:<20} {:>5.1}%",
+            action.to_string(),
+            100.0 * states as f64 / q.len() as f64
+        );
+    }
+
+    Ok(())
+}
+
+fn dump(model: &str, all: bool) -> Result<(), String> {
+    let (_, q) = load(model)?;
+    let optimal = data::q_to_optimal(&q);
+
+    let mut states: Vec<&GameState> = q.keys().collect();
+    states.sort();
+
+    let mut out = io::stdout().lock();
+    for state in states {
+        let actions = &q[state];
+        let written = if all {
+            let mut actions: Vec<(&Action, &f64)> = actions.iter().collect();
+            actions.sort_by(|a, b| b.1.total_cmp(a.1));
+            let actions: Vec<String> = actions
+                .into_iter()
+                .map(|(action, value)| format!("{action} {value:.4}"))
+                .collect();
+            writeln!(out, "{state} => {}", actions.join(", "))
+        } else if let Some(action) = optimal.get(state) {
+            writeln!(out, "{state} => {action} ({:.4})", actions[action])
+        } else {
+            Ok(())
+        };
+
+        match written {
+            Ok(()) => {}
+            // piped into e.g. head, which has seen enough
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+
+    Ok(())
+}
+
+fn diff(first: &str, second: &str, limit: Option<usize>) -> Result<(), String> {
+    let (_, a) = load(first)?;
+    let (_, b) = load(second)?;
+
+    let best_a = data::q_to_optimal(&a);
+    let best_b = data::q_to_optimal(&b);
+
+    let mut different: Vec<&GameState> = best_a
+        .iter()
+        .filter(|(state, action)| best_b.get(state).is_some_and(|other| other != *action))
+        .map(|(state, _)| state)
+        .collect();
+    different.sort();
+
+    let common = best_a.keys().filter(|s| best_b.contains_key(s)).count();
+    let only_first = best_a.len() - common;
+    let only_second = best_b.len() - common;
+
+    for state in different.iter().take(limit.unwrap_or(usize::MAX)) {
+        let value = |q: &Q<GameState>, action: &Action| q[*state][action];
+        println!(
+            "{state}\n    {first}: {} ({:.4})\n    {second}: {} ({:.4})",
+            best_a[*state],
+            value(&a, &best_a[*state]),
+            best_b[*state],
+            value(&b, &best_b[*state]),
+        );
+    }
+    if !different.is_empty() {
+        println!();
+    }
+
+    println!(
+        "{} of {common} common states have a different best action ({:.1}%)",
+        different.len(),
+        100.0 * different.len() as f64 / common.max(1) as f64
+    );
+    println!("{only_first} states are only in {first}, {only_second} only in {second}");
+
+    Ok(())
+}
+
+fn prune(model: &str, visits: &str, min_visits: u64, output: &str) -> Result<(), String> {
+    let (header, mut q) = load(model)?;
+    let checkpoint = data::bin_to_checkpoint::<GameState>(visits)
+        .map_err(|e| format!("Could not read checkpoint {visits}: {e}"))?;
+
+    let before = q.len();
+    q.retain(|state, _| {
+        let visited: u64 = checkpoint
+            .visits
+            .get(state)
+            .map_or(0, |counts| counts.values().sum());
+        visited >= min_visits
+    });
+
+    println!(
+        "kept {} of {before} states, visited at least {min_visits} times",
+        q.len()
+    );
+
+    save(&q, output, &header)
+}
+
+fn convert(model: &str, reduced: Option<bool>, output: &str) -> Result<(), String> {
+    let (mut header, q) = load(model)?;
+
+    if header.reduced && reduced == Some(false) {
+        println!("{model} is reduced, only the values of the best actions are known");
+    }
+    header.reduced = reduced.unwrap_or(header.reduced);
+
+    save(&q, output, &header)?;
+    println!(
+        "saved {output} as a {} model, format version {}",
+        if header.reduced { "reduced" } else { "full" },
+        fortify::model::FORMAT_VERSION
+    );
+
+    Ok(())
+}
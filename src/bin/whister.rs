@@ -1,43 +1,123 @@
 /*!
 This crate implements an AI which plays Colour Whist (nl: Kleurenwiezen).
 */
+use clap::Parser;
+use std::path::{Path, PathBuf};
 use whister::{
-    fortify,
-    game::Game, show,
+    fortify::{self, data::Pick},
+    game::Game,
+    render::{self, Style},
+    session::Session,
+    show,
+    tui::Tui,
+    ui::{Plain, Ui},
 };
 
+#[derive(Parser, Debug)]
+#[command(about = "Play colour whist against an AI", version)]
+struct Args {
+    /// the model to play against: a model file, a saved model, or easy, medium or hard
+    model: Option<String>,
+
+    /// how to draw the cards: ansi, unicode or ascii (detected from the terminal by default)
+    #[arg(long)]
+    style: Option<Style>,
+
+    /// practice: take back your last card by pressing [u]
+    #[arg(short, long)]
+    practice: bool,
+
+    /// export the scoreboard to this csv file, when the game ends
+    #[arg(long)]
+    csv: Option<PathBuf>,
+}
+
 fn main() {
+    let args = Args::parse();
+
+    if let Some(style) = args.style {
+        render::set_style(style);
+    }
+
     show::clear();
-    let mut game = Game::new();
-    game.add_human_players(1).unwrap();
 
-    let mut count = 0;
+    let resumed = resume();
+    let new_game = resumed.is_none();
+    let (mut game, mut model, mut count) = match resumed {
+        Some(session) => (session.game, session.model, session.deals),
+        None => {
+            let model = match &args.model {
+                Some(model) => Some(model.clone()),
+                None => match fortify::data::pick_model(false) {
+                    Ok(Pick::Model(name)) => Some(name),
+                    Ok(Pick::New) => None,
+                    Ok(Pick::Cancel) => return,
+                    Err(err) => {
+                        println!("Could not select a model: {err}");
+                        None
+                    }
+                },
+            };
+
+            let mut game = Game::new();
+            game.add_human_players(1).unwrap();
+            game.set_practice(args.practice).unwrap();
+            (game, model, 0)
+        }
+    };
 
-    let q = fortify::data::select_model(false);
+    let q = model
+        .as_ref()
+        .and_then(|name| match fortify::data::bin_to_q(name) {
+            Ok(q) => Some(q),
+            Err(err) => {
+                println!("Could not load the model: {err}");
+                None
+            }
+        });
 
     if q.is_none() {
+        model = None;
         println!("A rule based opponent will be used!");
         show::wait();
         show::wait();
     }
 
-    print!("\nNeed instructions? [Y/n]: \x1b[1m");
-    let answer = show::yes_or_no(true);
-    print!("\x1b[0m");
-    if answer {
-        Game::instructions();
+    if new_game {
+        print!("\nNeed instructions? [Y/n]: {}", render::ansi(render::BOLD));
+        let answer = show::yes_or_no(true);
+        print!("{}", render::ansi(render::RESET));
+        if answer {
+            Game::instructions();
+        }
     }
 
+    // full-screen in a terminal, lines of text when the output is piped
+    let mut ui: Box<dyn Ui> = match render::is_terminal() {
+        true => match Tui::start() {
+            Ok(tui) => Box::new(tui),
+            Err(err) => {
+                println!("Could not start the game in this terminal: {err}");
+                return;
+            }
+        },
+        false => Box::new(Plain::new()),
+    };
+
     loop {
-        game.play_deal(&q);
+        if !game.play_deal(ui.as_mut(), &q) {
+            // back to the normal screen, to tell the game is saved
+            drop(ui);
+            export_scores(&game, args.csv.as_deref());
+            save(Session::new(game, model, count));
+            return;
+        }
 
         game.new_deal();
-        game.show_scores();
+        game.paint(ui.as_mut());
+        ui.page("Scoreboard", game.scoreboard().lines());
 
-        print!("Play another round? [Y/n]: \x1b[1m");
-        let answer = show::yes_or_no(true);
-        print!("\x1b[0m");
-        if !answer {
+        if !ui.ask("Play another round?", true) {
             break;
         }
 
@@ -46,4 +126,80 @@ fn main() {
             break;
         }
     }
+
+    drop(ui);
+    game.show_scores();
+    export_scores(&game, args.csv.as_deref());
+}
+
+/// write the scoreboard to the csv file at `path`, if there is one
+fn export_scores(game: &Game, path: Option<&Path>) {
+    if let Some(path) = path {
+        match game.scoreboard().save_csv(path) {
+            Ok(()) => println!("The scores are exported to {}.", path.display()),
+            Err(err) => println!("Could not export the scores: {err}"),
+        }
+    }
+}
+
+/// save the quit game, unless it would replace a kept game the player wants to keep
+fn save(session: Session) {
+    // a game that was not resumed nor discarded is still saved
+    if let Ok(Some(kept)) = Session::load() {
+        print!(
+            "Replace the game you quit on {} with this one? [y/N]: {}",
+            kept.date(),
+            render::ansi(render::BOLD)
+        );
+        let answer = show::yes_or_no(false);
+        print!("{}", render::ansi(render::RESET));
+        if !answer {
+            println!(
+                "This game is not saved, the one of {} is kept.",
+                kept.date()
+            );
+            return;
+        }
+    }
+
+    match session.save() {
+        Ok(()) => println!("The game is saved, it can be resumed next time."),
+        Err(err) => println!("Could not save the game: {err}"),
+    }
+}
+
+/// offer to resume the game that was quit the last time
+fn resume() -> Option<Session> {
+    let session = match Session::load() {
+        Ok(session) => session?,
+        Err(err) => {
+            println!("Could not read the saved game: {err}");
+            return None;
+        }
+    };
+
+    println!(
+        "You quit a game on {}, against {}.",
+        session.date(),
+        session.model.as_deref().unwrap_or("a rule based opponent")
+    );
+    session.game.show_scores();
+    print!("Resume it? [Y/n]: {}", render::ansi(render::BOLD));
+    let resume = show::yes_or_no(true);
+    print!("{}", render::ansi(render::RESET));
+
+    // a resumed game is saved again when it is quit again
+    let discard = resume || {
+        print!("Discard it? [y/N]: {}", render::ansi(render::BOLD));
+        let answer = show::yes_or_no(false);
+        print!("{}", render::ansi(render::RESET));
+        answer
+    };
+    if discard {
+        if let Err(err) = Session::remove() {
+            println!("Could not remove the saved game: {err}");
+        }
+    }
+
+    resume.then_some(session)
 }
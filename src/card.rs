@@ -2,7 +2,8 @@
  * Playing card, meaning a suit and a number/score.
  */
 
-use crate::suit::Suit;
+use crate::{render, suit::Suit};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::Ordering;
 use std::fmt;
 
@@ -101,20 +102,23 @@ impl PartialOrd for Card {
 }
 
 impl fmt::Display for Card {
+    /// the card in the current style, see `render`
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut nb = self.number.to_string();
-
-        // special numbers
-        nb = match nb.as_str() {
-            "1" => "A".to_string(),
-            "10" => "T".to_string(),
-            "11" => "J".to_string(),
-            "12" => "Q".to_string(),
-            "13" => "K".to_string(),
-            _ => nb,
-        };
+        write!(f, "{}", render::style().card(self))
+    }
+}
+
+/// a card is serialized in its notation, like `"AH"` or `"TS"`, see `notation`
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.notation())
+    }
+}
 
-        write!(f, "\x1b[47;30m{}{}", nb, self.suit)
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let notation = String::deserialize(deserializer)?;
+        notation.parse().map_err(de::Error::custom)
     }
 }
 
@@ -127,12 +131,14 @@ impl Default for Card {
     }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<i32> for Card {
     fn into(self) -> i32 {
         self.score() as i32
     }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<i16> for Card {
     fn into(self) -> i16 {
         self.score() as i16
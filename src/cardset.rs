@@ -0,0 +1,397 @@
+/*!
+ * A set of cards as the bits of one `u64`, for fast card counting and simulation.
+ *
+ * Card `13 * suit + rank` is a bit, where rank 0 is a two and rank 12 an ace,
+ * so the bits of a suit are in the order of the card values.
+ */
+
+use crate::{card::Card, deck::Deck, render, suit::Suit};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign};
+
+const SUITS: [Suit; 4] = [Suit::Spades, Suit::Clubs, Suit::Diamonds, Suit::Hearts];
+
+/// the 13 bits of one suit, in the lowest position
+const SUIT_BITS: u64 = (1 << 13) - 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct CardSet(u64);
+
+impl CardSet {
+    pub const EMPTY: CardSet = CardSet(0);
+    pub const FULL: CardSet = CardSet((1 << 52) - 1);
+
+    pub fn new() -> Self {
+        Self::EMPTY
+    }
+
+    pub fn from_bits(bits: u64) -> Self {
+        CardSet(bits & Self::FULL.0)
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// the position of a card in the set, from 0 (two of spades) to 51 (ace of hearts)
+    pub fn index_of(card: &Card) -> u32 {
+        13 * card.suit as u32 + card.score() - 2
+    }
+
+    /// the card at a position in the set, see `index_of`
+    pub fn card_at(index: u32) -> Card {
+        let rank = index % 13;
+
+        Card {
+            suit: SUITS[(index / 13) as usize],
+            // an ace is number 1
+            number: if rank == 12 { 1 } else { rank as u8 + 2 },
+        }
+    }
+
+    /// all thirteen cards of a suit
+    pub fn suit_mask(suit: Suit) -> Self {
+        CardSet(SUIT_BITS << (13 * suit as u32))
+    }
+
+    /// the cards of the same suit as `card`, which have a higher value
+    pub fn higher_than(card: &Card) -> Self {
+        let index = Self::index_of(card);
+        let above = !((2u64 << index) - 1);
+
+        CardSet(above & Self::suit_mask(card.suit).0)
+    }
+
+    /// Add a card, returns whether it wasn't in the set yet.
+    pub fn insert(&mut self, card: &Card) -> bool {
+        let bit = 1u64 << Self::index_of(card);
+        let new = self.0 & bit == 0;
+        self.0 |= bit;
+        new
+    }
+
+    /// Remove a card, returns whether it was in the set.
+    pub fn remove(&mut self, card: &Card) -> bool {
+        let bit = 1u64 << Self::index_of(card);
+        let present = self.0 & bit != 0;
+        self.0 &= !bit;
+        present
+    }
+
+    pub fn contains(&self, card: &Card) -> bool {
+        self.0 & (1u64 << Self::index_of(card)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// is every card of this set also in `other`?
+    pub fn is_subset(&self, other: CardSet) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    /// only the cards of this suit
+    pub fn of_suit(&self, suit: Suit) -> Self {
+        *self & Self::suit_mask(suit)
+    }
+
+    /// the amount of cards of this suit
+    pub fn count(&self, suit: Suit) -> usize {
+        self.of_suit(suit).len()
+    }
+
+    pub fn has_suit(&self, suit: Suit) -> bool {
+        !self.of_suit(suit).is_empty()
+    }
+
+    /// the card with the lowest position, e.g. the first card of a sorted hand
+    pub fn first(&self) -> Option<Card> {
+        match self.0 {
+            0 => None,
+            bits => Some(Self::card_at(bits.trailing_zeros())),
+        }
+    }
+
+    /// the card with the highest position
+    pub fn last(&self) -> Option<Card> {
+        match self.0 {
+            0 => None,
+            bits => Some(Self::card_at(63 - bits.leading_zeros())),
+        }
+    }
+
+    /// the highest card of a suit
+    pub fn highest_of_suit(&self, suit: Suit) -> Option<Card> {
+        self.of_suit(suit).last()
+    }
+
+    /// the lowest card of a suit
+    pub fn lowest_of_suit(&self, suit: Suit) -> Option<Card> {
+        self.of_suit(suit).first()
+    }
+
+    /// The highest card following `Card::higher`: trumps are higher than other suits,
+    /// other suits are compared by value only.
+    /// Of equally high cards, the one of the last suit is taken.
+    pub fn highest(&self, trump: Suit) -> Option<Card> {
+        if let Some(card) = self.highest_of_suit(trump) {
+            return Some(card);
+        }
+
+        SUITS
+            .iter()
+            .filter_map(|suit| self.highest_of_suit(*suit))
+            .max_by_key(Card::score)
+    }
+
+    /// The lowest card following `Card::higher`, see `highest`.
+    /// Of equally low cards, the one of the first suit is taken.
+    pub fn lowest(&self, trump: Suit) -> Option<Card> {
+        let others = *self - Self::suit_mask(trump);
+        if others.is_empty() {
+            return self.lowest_of_suit(trump);
+        }
+
+        SUITS
+            .iter()
+            .filter_map(|suit| others.lowest_of_suit(*suit))
+            .min_by_key(Card::score)
+    }
+
+    /// the cards in the set, from the lowest position to the highest
+    pub fn iter(&self) -> Iter {
+        Iter(self.0)
+    }
+
+    /// a sorted deck with the cards of this set
+    pub fn to_deck(&self) -> Deck {
+        Deck::new_from(self.iter().collect())
+    }
+}
+
+/// Iterator over the cards of a `CardSet`, see `CardSet::iter`.
+pub struct Iter(u64);
+
+impl Iterator for Iter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let index = self.0.trailing_zeros();
+        // clear the lowest bit
+        self.0 &= self.0 - 1;
+
+        Some(CardSet::card_at(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Iter {
+    fn next_back(&mut self) -> Option<Card> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let index = 63 - self.0.leading_zeros();
+        self.0 &= !(1u64 << index);
+
+        Some(CardSet::card_at(index))
+    }
+}
+
+impl ExactSizeIterator for Iter {}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> Self {
+        let mut set = CardSet::EMPTY;
+        for card in iter {
+            set.insert(&card);
+        }
+        set
+    }
+}
+
+impl<'a> FromIterator<&'a Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = &'a Card>>(iter: I) -> Self {
+        let mut set = CardSet::EMPTY;
+        for card in iter {
+            set.insert(card);
+        }
+        set
+    }
+}
+
+impl From<&Deck> for CardSet {
+    fn from(deck: &Deck) -> Self {
+        deck.iter().collect()
+    }
+}
+
+impl From<CardSet> for Deck {
+    fn from(set: CardSet) -> Self {
+        set.to_deck()
+    }
+}
+
+/// a set is serialized as its cards, from the lowest position to the highest
+impl Serialize for CardSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for CardSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Vec::<Card>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+
+    fn bitor(self, other: CardSet) -> CardSet {
+        CardSet(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for CardSet {
+    fn bitor_assign(&mut self, other: CardSet) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = CardSet;
+
+    fn bitand(self, other: CardSet) -> CardSet {
+        CardSet(self.0 & other.0)
+    }
+}
+
+impl BitAndAssign for CardSet {
+    fn bitand_assign(&mut self, other: CardSet) {
+        self.0 &= other.0;
+    }
+}
+
+impl Sub for CardSet {
+    type Output = CardSet;
+
+    fn sub(self, other: CardSet) -> CardSet {
+        CardSet(self.0 & !other.0)
+    }
+}
+
+impl SubAssign for CardSet {
+    fn sub_assign(&mut self, other: CardSet) {
+        self.0 &= !other.0;
+    }
+}
+
+/// all the cards that are not in the set
+impl Not for CardSet {
+    type Output = CardSet;
+
+    fn not(self) -> CardSet {
+        CardSet(!self.0 & Self::FULL.0)
+    }
+}
+
+impl fmt::Display for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let style = render::style();
+        let cards: Vec<String> = self
+            .iter()
+            .map(|card| format!("{}{}", style.card(&card), style.ansi(render::RESET)))
+            .collect();
+        write!(f, "{{{}}}", cards.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(suit: Suit, number: u8) -> Card {
+        Card { suit, number }
+    }
+
+    #[test]
+    fn indices_round_trip() {
+        for index in 0..52 {
+            assert_eq!(CardSet::index_of(&CardSet::card_at(index)), index);
+        }
+        assert_eq!(CardSet::card_at(12), card(Suit::Spades, 1));
+        assert_eq!(CardSet::FULL.len(), 52);
+        assert_eq!(CardSet::suit_mask(Suit::Diamonds).len(), 13);
+    }
+
+    #[test]
+    fn deck_conversions() {
+        let mut deck = Deck::new_full();
+        deck.sort();
+        let set = CardSet::from(&deck);
+
+        assert_eq!(set, CardSet::FULL);
+        assert_eq!(set.iter().collect::<Vec<_>>(), deck.cards);
+        assert_eq!(Deck::from(set).cards, deck.cards);
+    }
+
+    #[test]
+    fn higher_cards_of_suit() {
+        let set = CardSet::higher_than(&card(Suit::Clubs, 12));
+
+        let cards: Vec<Card> = set.iter().collect();
+        assert_eq!(cards, vec![card(Suit::Clubs, 13), card(Suit::Clubs, 1)]);
+        assert!(CardSet::higher_than(&card(Suit::Hearts, 1)).is_empty());
+    }
+
+    #[test]
+    fn highest_and_lowest_follow_card_order() {
+        let set: CardSet = [
+            card(Suit::Spades, 9),
+            card(Suit::Clubs, 1),
+            card(Suit::Diamonds, 1),
+            card(Suit::Diamonds, 3),
+            card(Suit::Hearts, 2),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(set.highest(Suit::Hearts), Some(card(Suit::Hearts, 2)));
+        assert_eq!(set.lowest(Suit::Hearts), Some(card(Suit::Diamonds, 3)));
+
+        let no_trumps = set - CardSet::suit_mask(Suit::Hearts);
+        // equally high, the last suit wins
+        assert_eq!(
+            no_trumps.highest(Suit::Hearts),
+            Some(card(Suit::Diamonds, 1))
+        );
+        assert_eq!(no_trumps.count(Suit::Diamonds), 2);
+        assert_eq!(!no_trumps & no_trumps, CardSet::EMPTY);
+    }
+}
@@ -2,18 +2,21 @@
  * Pack of cards, not necessarily a full deck. Lots of functionality.
  */
 
-use crate::{card::Card, player::Player, suit::Suit};
+use crate::{card::Card, cardset::CardSet, player::Player, render, suit::Suit};
 use rand::seq::SliceRandom;
 use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{cmp::min, fmt};
 
 pub type CardID = usize;
 
-#[derive(Hash, Eq, PartialEq)]
+#[derive(Clone, Hash, Eq, PartialEq)]
 pub struct Deck {
     pub cards: Vec<Card>,
     selected: CardID,
     suit_amounts: [usize; 4],
+    /// the same cards as `cards`, to look up quickly
+    set: CardSet,
     curr: usize,
 }
 
@@ -62,6 +65,7 @@ impl Deck {
             cards,
             selected: usize::MAX,
             suit_amounts: [13, 13, 13, 13],
+            set: CardSet::FULL,
             curr: 0,
         }
     }
@@ -84,6 +88,11 @@ impl Deck {
         self.suit_amounts[*suit as usize] > 0
     }
 
+    /// the cards of this deck as a set, without their order
+    pub fn set(&self) -> CardSet {
+        self.set
+    }
+
     pub fn suit_of(&self, index: CardID) -> Suit {
         self.cards[index].suit
     }
@@ -140,7 +149,12 @@ impl Deck {
     }
 
     pub fn shuffle(&mut self) {
-        self.cards.shuffle(&mut rand::thread_rng());
+        self.shuffle_with(&mut rand::thread_rng());
+    }
+
+    /// shuffle with the given source of randomness, e.g. to get the same deals every time
+    pub fn shuffle_with<R: rand::Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
     }
 
     /// sort the cards by Suits first, then by ascending number
@@ -193,7 +207,7 @@ impl Deck {
     }
 
     pub fn contains(&self, card: &Card) -> bool {
-        self.cards.contains(card)
+        self.set.contains(card)
     }
 
     pub fn id_of(&self, card: &Card) -> Option<CardID> {
@@ -205,6 +219,7 @@ impl Deck {
     /// This is useful when playing a game, and a player puts a card from its deck to the table's deck.
     pub fn remove(&mut self, index: CardID) -> Card {
         self.suit_amounts[self.cards[index].suit as usize] -= 1;
+        self.set.remove(&self.cards[index]);
         if index == self.selected && index != 0 {
             self.selected -= 1;
         }
@@ -218,6 +233,7 @@ impl Deck {
     /// *Note: consumes the card!*
     pub fn add(&mut self, card: Card) {
         self.suit_amounts[card.suit as usize] += 1;
+        self.set.insert(&card);
         self.cards.push(card);
     }
 
@@ -272,6 +288,7 @@ impl Deck {
             .for_each(|c| amounts[c.suit as usize] += 1);
 
         self.suit_amounts = amounts;
+        self.set = self.cards.iter().collect();
     }
 
     pub fn get_suit_amount(&self, suit: &Suit) -> usize {
@@ -292,7 +309,9 @@ impl Deck {
 
     /// returns (y, x) where y is the suit index and x is the index within that suit
     fn selected_to_coordinate(&self) -> (usize, usize) {
-        let suit = self.card(self.selected).suit;
+        // nothing selected yet (`usize::MAX`) is the last card, like in `card`
+        let selected = self.selected % self.cards.len();
+        let suit = self.card(selected).suit;
 
         let y = suit as usize;
 
@@ -301,7 +320,7 @@ impl Deck {
             start += self.suit_amounts[s];
         }
 
-        let x = self.selected - start;
+        let x = selected - start;
 
         (y, x)
     }
@@ -317,46 +336,71 @@ impl Deck {
     }
 
     pub fn show_as_table(&self, me: usize, first: usize, winner: usize) {
-        for player in 0..4 {
-            // print 15 characters wide + 2 padding per player
-            if player == winner {
-                print!("\x1b[92m");
-            }
-
-            if me == player {
-                print!("╭╴{}╮", pad("You╶", '─', 9));
-            } else {
-                print!("╭╴{}╮", pad(&format!("Player {player}╶"), '─', 9));
-            }
-
-            print!(" \x1b[0m");
+        for line in self.table_lines(me, first, winner) {
+            println!("{line}");
         }
+        println!()
+    }
 
-        println!();
+    /// The cards on the table, with a box per player: the names on the first line,
+    /// the cards on the second one. `first` played the first card, `winner` is drawn in green.
+    pub fn table_lines(&self, me: usize, first: usize, winner: usize) -> [String; 2] {
+        let style = render::style();
+        let frame = style.frame();
+        // the winner is drawn in green
+        let colour = |player: usize| match player == winner {
+            true => style.ansi("\x1b[92m"),
+            false => "",
+        };
 
-        for player in 0..4 {
-            if player == winner {
-                print!("\x1b[92m");
-            }
+        let mut names = String::new();
+        let mut cards = String::new();
 
-            print!("╰───");
+        for player in 0..4 {
+            // 12 characters wide + 1 padding per player
+            let name = match me == player {
+                true => format!("You{}", frame.right_end),
+                false => format!("Player {player}{}", frame.right_end),
+            };
+
+            names.push_str(&format!(
+                "{}{}{}{}{} {}",
+                colour(player),
+                frame.top_left,
+                frame.left_end,
+                pad(&name, frame.horizontal, 9),
+                frame.top_right,
+                style.ansi(render::RESET)
+            ));
+
+            cards.push_str(&format!(
+                "{}{}{}",
+                colour(player),
+                frame.bottom_left,
+                style.line(3)
+            ));
 
             if has_played(player, first, self.cards.len()) {
-                print!("╴{}", self.cards[dist(player, first)]);
-                if player == winner {
-                    print!("\x1b[92m╶");
-                } else {
-                    print!("╶");
-                }
+                cards.push_str(&format!(
+                    "{}{}{}{}",
+                    frame.left_end,
+                    self.cards[dist(player, first)],
+                    colour(player),
+                    frame.right_end
+                ));
             } else {
-                print!("────");
+                cards.push_str(&style.line(4));
             }
 
-            print!("───╯ \x1b[0m");
+            cards.push_str(&format!(
+                "{}{} {}",
+                style.line(3),
+                frame.bottom_right,
+                style.ansi(render::RESET)
+            ));
         }
 
-        println!();
-        println!()
+        [names, cards]
     }
 }
 
@@ -470,32 +514,24 @@ impl Default for Deck {
     }
 }
 
+/// the hand in the current style, see `render`
 impl fmt::Display for Deck {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // do not show an empty deck!
-        if self.is_empty() {
-            return write!(f, "  Empty");
-        }
-
-        write!(f, "\x1b[2m╭╴\x1b[0m")?;
-
-        let mut current_suit = self.cards[0].suit;
-
-        for card in &self.cards {
-            if card.suit != current_suit {
-                write!(f, "\n\x1b[2m│\x1b[0m ")?;
-                current_suit = card.suit;
-            }
+        write!(f, "{}", render::style().hand(self))
+    }
+}
 
-            // show card, selected card shown differently
-            if self.id_of(card).unwrap_or(0) == self.selected {
-                write!(f, "\x1b[7m{}, \x1b[0m", card)?;
-            } else {
-                write!(f, "{}, ", card)?;
-            }
-        }
+/// A deck is serialized as its cards, in order.
+/// Which card is selected is left out, it only matters to the UI.
+impl Serialize for Deck {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.cards.serialize(serializer)
+    }
+}
 
-        write!(f, "\n\x1b[2m╰╴count: {}\x1b[0m", self.size())
+impl<'de> Deserialize<'de> for Deck {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Deck::new_from(Vec::deserialize(deserializer)?))
     }
 }
 
@@ -523,6 +559,19 @@ mod tests {
         deck
     }
 
+    #[test]
+    fn shuffle_with_seed_is_reproducible() {
+        use rand::SeedableRng;
+
+        let mut deck = init_deck();
+        let mut other = init_deck();
+        deck.shuffle_with(&mut rand_chacha::ChaCha8Rng::seed_from_u64(7));
+        other.shuffle_with(&mut rand_chacha::ChaCha8Rng::seed_from_u64(7));
+
+        assert_eq!(deck.cards, other.cards);
+        assert_ne!(deck.cards, init_deck().cards);
+    }
+
     #[test]
     fn highest_one_suit() {
         let deck = init_deck();
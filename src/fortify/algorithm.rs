@@ -0,0 +1,217 @@
+/*!
+The temporal difference algorithms a `QLearner` can use.
+
+They only differ in how the value of the state they bootstrap from is estimated:
+- Q-learning uses the best known action (off-policy),
+- Double Q-learning picks the best action with one table, but evaluates it with the other,
+- SARSA uses the action that was actually taken next (on-policy),
+- Expected SARSA uses the expected value under the behaviour policy.
+*/
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::episode::Bootstrap;
+use super::{action_values, best_action_score, Policy, State, Q};
+
+/// The algorithm used to learn the Q values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Algorithm {
+    /// one-step Q-learning with a single table
+    #[default]
+    QLearning,
+    /// Q-learning with two tables, to reduce overestimation of the values
+    DoubleQLearning,
+    /// on-policy: bootstrap with the action that is actually taken next
+    Sarsa,
+    /// on-policy: bootstrap with the expected value under the behaviour policy
+    ExpectedSarsa,
+}
+
+impl Algorithm {
+    /// all algorithms, useful to compare them
+    pub fn all() -> [Algorithm; 4] {
+        [
+            Algorithm::QLearning,
+            Algorithm::DoubleQLearning,
+            Algorithm::Sarsa,
+            Algorithm::ExpectedSarsa,
+        ]
+    }
+
+    /// does this algorithm train two tables?
+    pub fn is_double(&self) -> bool {
+        *self == Algorithm::DoubleQLearning
+    }
+
+    /// Estimate the value of the bootstrap state.
+    ///
+    /// `q` is the table that is being updated, `other` is only used by double Q-learning.
+    pub fn bootstrap_value<S: State>(
+        &self,
+        q: &Q<S>,
+        other: &Q<S>,
+        bootstrap: &Bootstrap<S>,
+        policy: &Policy,
+        iteration: u64,
+        initial: f64,
+    ) -> f64 {
+        match self {
+            Algorithm::QLearning => match best_action_score(q, &bootstrap.state) {
+                Ok(best) => best.1,
+                Err(_) => initial,
+            },
+            Algorithm::DoubleQLearning => match best_action_score(q, &bootstrap.state) {
+                // select with this table, evaluate with the other
+                Ok((best, _)) => other
+                    .get(&bootstrap.state)
+                    .and_then(|m| m.get(&best))
+                    .copied()
+                    .unwrap_or(initial),
+                Err(_) => initial,
+            },
+            Algorithm::Sarsa => q
+                .get(&bootstrap.state)
+                .and_then(|m| m.get(&bootstrap.action))
+                .copied()
+                .unwrap_or(initial),
+            Algorithm::ExpectedSarsa => {
+                let values = action_values(q, &bootstrap.state, &bootstrap.actions, initial);
+                policy
+                    .probabilities(&values, iteration)
+                    .iter()
+                    .zip(values.iter())
+                    .map(|(p, v)| p * v)
+                    .sum()
+            }
+        }
+    }
+}
+
+/// Combine the two tables of double Q-learning into one, by averaging their values.
+pub fn average<S: State>(a: &Q<S>, b: &Q<S>, initial: f64) -> Q<S> {
+    let mut averaged = a.clone();
+
+    for (state, actions) in b {
+        let entry = averaged.entry(*state).or_default();
+        for (action, value) in actions {
+            let mine = a
+                .get(state)
+                .and_then(|m| m.get(action))
+                .copied()
+                .unwrap_or(initial);
+            entry.insert(*action, (mine + value) / 2.0);
+        }
+    }
+
+    // values only known by the first table
+    for (state, actions) in a {
+        for (action, value) in actions {
+            let in_b = b.get(state).is_some_and(|m| m.contains_key(action));
+            if !in_b {
+                averaged
+                    .entry(*state)
+                    .or_default()
+                    .insert(*action, (value + initial) / 2.0);
+            }
+        }
+    }
+
+    averaged
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Algorithm::QLearning => "q-learning",
+            Algorithm::DoubleQLearning => "double-q-learning",
+            Algorithm::Sarsa => "sarsa",
+            Algorithm::ExpectedSarsa => "expected-sarsa",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::testing::state;
+    use crate::gamestate::{Action, GameState};
+    use std::collections::HashMap;
+
+    fn table(values: &[(Action, f64)]) -> Q<GameState> {
+        let mut q = HashMap::new();
+        q.insert(state(0), values.iter().cloned().collect());
+        q
+    }
+
+    fn bootstrap(action: Action) -> Bootstrap<GameState> {
+        Bootstrap {
+            state: state(0),
+            action,
+            actions: vec![Action::RaiseLow, Action::RaiseHigh],
+        }
+    }
+
+    #[test]
+    fn double_q_evaluates_with_other_table() {
+        let a = table(&[(Action::RaiseLow, 1.0), (Action::RaiseHigh, 0.0)]);
+        let b = table(&[(Action::RaiseLow, 0.2), (Action::RaiseHigh, 0.9)]);
+
+        let value = Algorithm::DoubleQLearning.bootstrap_value(
+            &a,
+            &b,
+            &bootstrap(Action::RaiseHigh),
+            &Policy::Random,
+            0,
+            0.5,
+        );
+
+        assert_eq!(value, 0.2);
+    }
+
+    #[test]
+    fn sarsa_uses_next_action() {
+        let q = table(&[(Action::RaiseLow, 1.0), (Action::RaiseHigh, 0.0)]);
+
+        let value = Algorithm::Sarsa.bootstrap_value(
+            &q,
+            &q,
+            &bootstrap(Action::RaiseHigh),
+            &Policy::Random,
+            0,
+            0.5,
+        );
+
+        assert_eq!(value, 0.0);
+    }
+
+    #[test]
+    fn expected_sarsa_averages_under_policy() {
+        let q = table(&[(Action::RaiseLow, 1.0), (Action::RaiseHigh, 0.0)]);
+
+        let value = Algorithm::ExpectedSarsa.bootstrap_value(
+            &q,
+            &q,
+            &bootstrap(Action::RaiseHigh),
+            &Policy::Random,
+            0,
+            0.5,
+        );
+
+        assert_eq!(value, 0.5);
+    }
+
+    #[test]
+    fn average_of_tables() {
+        let a = table(&[(Action::RaiseLow, 1.0)]);
+        let b = table(&[(Action::RaiseLow, 0.0), (Action::RaiseHigh, 1.0)]);
+
+        let averaged = average(&a, &b, 0.0);
+
+        assert_eq!(averaged[&state(0)][&Action::RaiseLow], 0.5);
+        assert_eq!(averaged[&state(0)][&Action::RaiseHigh], 0.5);
+    }
+}
@@ -0,0 +1,82 @@
+/*!
+The models in `data/models`, embedded in the binary so they can be played against
+without copying them to the data directory first.
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::data::{self, DataError};
+use super::{ModelHeader, State, Q};
+
+/// The built-in models, from weakest to strongest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    /// the name of the model, which is also how it is asked for
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    /// the model file, as it is in `data/models`
+    pub fn bytes(&self) -> &'static [u8] {
+        match self {
+            Difficulty::Easy => include_bytes!("../../data/models/easy.bin"),
+            Difficulty::Medium => include_bytes!("../../data/models/medium.bin"),
+            Difficulty::Hard => include_bytes!("../../data/models/hard.bin"),
+        }
+    }
+
+    /// the built-in model with this name, if there is one
+    pub fn from_name(name: &str) -> Option<Self> {
+        Difficulty::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    pub fn load_model<S: State>(&self) -> Result<(ModelHeader, Q<S>), DataError> {
+        data::decode_model(self.bytes())
+    }
+
+    pub fn load<S: State>(&self) -> Result<Q<S>, DataError> {
+        self.load_model().map(|(_, q)| q)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::from_name(s).ok_or_else(|| format!("{s} is not easy, medium or hard"))
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::GameState;
+
+    #[test]
+    fn bundled_models_load() {
+        for difficulty in Difficulty::ALL {
+            let q: Q<GameState> = difficulty.load().unwrap();
+            assert!(!q.is_empty(), "{difficulty} is empty");
+        }
+        assert_eq!("hard".parse(), Ok(Difficulty::Hard));
+    }
+}
@@ -0,0 +1,50 @@
+/*!
+Checkpoints are snapshots of a training in progress, which can be used to resume it later.
+
+They are written periodically by `QLearner::train` and `QLearner::train_multi_agent`
+when enabled with `QLearner::enable_checkpoints`, and stored using `fortify::data`.
+*/
+
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::{Hyperparameters, State, Visits, Q};
+
+/// Everything needed to continue a training where it left off.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "S: State")]
+pub struct Checkpoint<S: State> {
+    pub q: Q<S>,
+    /// second table of double Q-learning, empty for the other algorithms
+    pub q_b: Q<S>,
+    pub visits: Visits<S>,
+    /// the table of every seat of a multi-agent training with separate tables
+    pub seat_tables: Vec<Q<S>>,
+    /// amount of iterations that have been done
+    pub current_iter: u64,
+    /// amount of iterations the training should reach
+    pub iterations: u64,
+    pub hyperparameters: Hyperparameters,
+    /// state of the random number generator of the learner
+    pub rng: ChaCha8Rng,
+}
+
+/// When and where checkpoints are written during training.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckpointConfig {
+    /// name of the checkpoint in the data directory
+    pub name: String,
+    /// write a checkpoint every this many iterations
+    pub every_iterations: Option<u64>,
+    /// write a checkpoint every time this much time has passed
+    pub every: Option<Duration>,
+}
+
+impl CheckpointConfig {
+    /// should a new checkpoint be written, given the progress since the last one?
+    pub fn due(&self, iterations: u64, elapsed: Duration) -> bool {
+        self.every_iterations.is_some_and(|n| iterations >= n)
+            || self.every.is_some_and(|d| elapsed >= d)
+    }
+}
@@ -1,36 +1,134 @@
+use bincode::Options;
 use dirs::data_dir;
 use flate2::read::ZlibDecoder;
 use flate2::write::ZlibEncoder;
 use flate2::Compression;
 use std::{
     collections::HashMap,
+    env, fmt,
     fs::{self, File},
-    io::{stdin, stdout, BufReader, Read, Write},
-    path::PathBuf,
-    process::exit,
+    io::{self, stdin, stdout, BufReader, Read, Stdout, Write},
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
+use termion::{
+    event::Key,
+    input::TermRead,
+    raw::{IntoRawMode, RawTerminal},
 };
-use termion::{event::Key, input::TermRead, raw::IntoRawMode};
 
-use crate::show;
+use crate::{render, show};
 
+use super::bundled::Difficulty;
+use super::model::{ModelHeader, FORMAT_VERSION, MAGIC};
+use super::Checkpoint;
 use super::State;
+use super::Visits;
 use super::Q;
 
+/// environment variable that overrides the data directory
+pub const DATA_DIR_VAR: &str = "WHISTER_DATA_DIR";
+
+/// Everything that can go wrong while reading or writing models and checkpoints.
+#[derive(Debug)]
+pub enum DataError {
+    /// reading or writing a file (or the terminal) failed
+    Io(io::Error),
+    /// the compressed data is damaged
+    Decompress(io::Error),
+    /// the data could not be turned into bytes
+    Serialize(bincode::Error),
+    /// the bytes are not what they should be, e.g. a table with another state layout
+    Deserialize(bincode::Error),
+    /// the file ends before the header does
+    Truncated,
+    /// the model file has a format version that can't be read
+    Version { found: u32, supported: u32 },
+    /// the model holds another type of states than the one asked for
+    StateMismatch { found: String, expected: String },
+    /// there is no directory to store data in on this system
+    NoDataDir,
+    /// no saved model, file or built-in model has this name
+    NotFound(String),
+    /// a JSON file, like a saved session, can't be read or written
+    Json(serde_json::Error),
+    /// the data can be read, but doesn't hold together, e.g. a saved game with missing cards
+    Invalid(String),
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::Io(err) => write!(f, "{err}"),
+            DataError::Decompress(err) => write!(f, "could not decompress: {err}"),
+            DataError::Serialize(err) => write!(f, "could not serialize: {err}"),
+            DataError::Deserialize(err) => write!(f, "could not deserialize: {err}"),
+            DataError::Truncated => write!(f, "the file is truncated"),
+            DataError::Version { found, supported } => write!(
+                f,
+                "the model has format version {found}, only version {supported} can be read"
+            ),
+            DataError::StateMismatch { found, expected } => {
+                write!(f, "the model holds {found} states, not {expected}")
+            }
+            DataError::NoDataDir => write!(f, "could not find a data directory"),
+            DataError::NotFound(name) => write!(f, "there is no model {name}"),
+            DataError::Json(err) => write!(f, "invalid json: {err}"),
+            DataError::Invalid(reason) => write!(f, "invalid data: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for DataError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DataError::Io(err) | DataError::Decompress(err) => Some(err),
+            DataError::Serialize(err) | DataError::Deserialize(err) => Some(err),
+            DataError::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DataError {
+    fn from(err: io::Error) -> Self {
+        DataError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for DataError {
+    fn from(err: serde_json::Error) -> Self {
+        DataError::Json(err)
+    }
+}
+
+impl From<csv::Error> for DataError {
+    fn from(err: csv::Error) -> Self {
+        DataError::Io(err.into())
+    }
+}
+
+/// What was picked in the model selection menu.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Selection<S: State> {
+    /// a saved model
+    Model(Q<S>),
+    /// no saved model: "create new" was picked, or there are no models to pick from
+    New,
+    /// the menu was closed without picking anything
+    Cancel,
+}
+
 pub fn q_to_optimal<S: State>(q: &Q<S>) -> HashMap<S, S::A> {
-    let mut optimal_action = HashMap::new();
-    q.iter().for_each(|test| {
-        optimal_action.insert(
-            *test.0,
-            *q.get(test.0)
-                .unwrap()
+    // states without any action values have no best action
+    q.iter()
+        .filter_map(|(state, actions)| {
+            let best = actions
                 .iter()
-                .max_by(|score1, score2| score1.1.partial_cmp(score2.1).unwrap())
-                .unwrap()
-                .0,
-        );
-    });
-
-    optimal_action
+                .max_by(|score1, score2| score1.1.total_cmp(score2.1))?;
+            Some((*state, *best.0))
+        })
+        .collect()
 }
 
 fn optimal_to_q<S: State>(optimal: HashMap<S, S::A>) -> Q<S> {
@@ -45,216 +143,563 @@ fn optimal_to_q<S: State>(optimal: HashMap<S, S::A>) -> Q<S> {
     q
 }
 
-fn get_data_dir() -> Result<std::path::PathBuf, ()> {
-    let mut data_dir = data_dir().expect("Could not find a data directory");
-    data_dir.push("whister");
+/// Where models and checkpoints are stored:
+/// `$WHISTER_DATA_DIR` if it is set, otherwise `whister` in the data directory of the user.
+pub fn get_data_dir() -> Result<PathBuf, DataError> {
+    let data_dir = match env::var_os(DATA_DIR_VAR) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => data_dir().ok_or(DataError::NoDataDir)?.join("whister"),
+    };
 
     if !data_dir.exists() {
-        if fs::create_dir_all(&data_dir).is_ok() {
-            Ok(data_dir)
-        } else {
-            Err(())
-        }
-    } else {
-        Ok(data_dir)
+        fs::create_dir_all(&data_dir)?;
     }
+
+    Ok(data_dir)
 }
 
-fn list_data() -> Vec<String> {
-    let data_dir = get_data_dir().expect("Should get data directory");
-    let paths = fs::read_dir(data_dir).unwrap();
+/// names of the files in `dir` with this extension
+fn list_data_with(dir: &Path, extension: &str) -> Result<Vec<String>, DataError> {
+    let mut names = Vec::new();
 
-    let mut models = Vec::new();
-    for path in paths {
-        let what = path.unwrap().file_name();
-        let model_file_name = what.to_str().unwrap();
-        let model_file_name_split: Vec<&str> = model_file_name.split('.').collect();
-        models.push(String::from(model_file_name_split[0]));
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == extension) {
+            if let Some(name) = path.file_stem().and_then(|n| n.to_str()) {
+                names.push(name.to_string());
+            }
+        }
     }
 
-    models
+    names.sort();
+    Ok(names)
 }
 
-fn model_name_to_path(model_name: &str) -> PathBuf {
-    let mut data_dit = get_data_dir().unwrap();
+/// names of all the models in the data directory
+pub fn list_data() -> Result<Vec<String>, DataError> {
+    list_data_with(&get_data_dir()?, "bin")
+}
+
+fn model_name_to_path(model_name: &str) -> Result<PathBuf, DataError> {
+    Ok(get_data_dir()?.join(format!("{}.bin", model_name)))
+}
 
-    data_dit.push(format!("{}.bin", model_name));
+fn checkpoint_name_to_path(checkpoint_name: &str) -> Result<PathBuf, DataError> {
+    Ok(get_data_dir()?.join(format!("{}.ckpt", checkpoint_name)))
+}
 
-    data_dit
+/// Write `q` with its header in the model file format, see `fortify::model`.
+/// The table is reduced to the best action of every state if the header says so.
+pub fn encode_model<S: State>(q: &Q<S>, header: &ModelHeader) -> Result<Vec<u8>, DataError> {
+    let table = match header.reduced {
+        true => bincode::serialize(&q_to_optimal(q)),
+        false => bincode::serialize(q),
+    }
+    .map_err(DataError::Serialize)?;
+    let header_bytes = bincode::serialize(header).map_err(DataError::Serialize)?;
+
+    let mut bytes = Vec::with_capacity(table.len() / 4 + header_bytes.len() + 18);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(header_bytes.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&header_bytes);
+
+    let mut encoder = ZlibEncoder::new(bytes, Compression::best());
+    encoder.write_all(&table)?;
+    Ok(encoder.finish()?)
 }
 
-fn get_save_file(file_name: &str) -> Result<std::fs::File, ()> {
-    let file = model_name_to_path(file_name);
-    // create the file
-    let file = File::create(file).map_err(|_| ())?;
+/// Read a model file, with or without header.
+///
+/// Files without header get one that says what could be found out:
+/// whether the table is reduced, and nothing about its training.
+pub fn decode_model<S: State>(bytes: &[u8]) -> Result<(ModelHeader, Q<S>), DataError> {
+    let Some(rest) = bytes.strip_prefix(MAGIC) else {
+        return decode_legacy(bytes);
+    };
+
+    let (version, rest) = split_le::<4>(rest)?;
+    let version = u32::from_le_bytes(version);
+    if version != FORMAT_VERSION {
+        return Err(DataError::Version {
+            found: version,
+            supported: FORMAT_VERSION,
+        });
+    }
+
+    let (length, rest) = split_le::<8>(rest)?;
+    let length = u64::from_le_bytes(length) as usize;
+    if rest.len() < length {
+        return Err(DataError::Truncated);
+    }
+    let (header_bytes, compressed) = rest.split_at(length);
+
+    let header: ModelHeader = bincode::deserialize(header_bytes).map_err(DataError::Deserialize)?;
+    header.check_state::<S>()?;
+
+    let table = decompress(compressed)?;
+    let q = match header.reduced {
+        true => strict().deserialize(&table).map(optimal_to_q),
+        false => strict().deserialize(&table),
+    }
+    .map_err(DataError::Deserialize)?;
 
-    // return file
-    Ok(file)
+    Ok((header, q))
 }
 
-fn get_data(file_name: &str) -> Option<Vec<u8>> {
-    let file = model_name_to_path(file_name);
+/// a model from before the header existed: just a compressed table, full or reduced
+fn decode_legacy<S: State>(bytes: &[u8]) -> Result<(ModelHeader, Q<S>), DataError> {
+    let table = decompress(bytes)?;
 
-    let file = match File::open(file) {
-        Ok(it) => it,
-        Err(_) => return None,
+    // a table of the wrong kind hardly ever deserializes without bytes to spare
+    // when the model was made isn't known either
+    let header = |reduced| ModelHeader {
+        created: 0,
+        ..ModelHeader::new::<S>(reduced)
     };
 
-    let mut reader = BufReader::new(file);
-    let mut serialized = Vec::new();
+    if let Ok(q) = strict().deserialize::<Q<S>>(&table) {
+        return Ok((header(false), q));
+    }
+    let optimal: HashMap<S, S::A> = strict()
+        .deserialize(&table)
+        .map_err(DataError::Deserialize)?;
 
-    reader.read_to_end(&mut serialized).unwrap();
+    Ok((header(true), optimal_to_q(optimal)))
+}
 
-    Some(serialized)
+/// bincode as it is used to write the tables, but refusing leftover bytes
+fn strict() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
 }
 
-pub fn q_to_bin<S: State>(q: &Q<S>, name: String, reduced: bool) -> std::io::Result<()> {
-    let serialized = match reduced {
-        true => {
-            let optimal = q_to_optimal(q);
-            bincode::serialize(&optimal).expect("Should serialize reduced Q")
-        }
-        false => bincode::serialize(q).expect("Should serialize unreduced Q"),
-    };
+fn decompress(compressed: impl Read) -> Result<Vec<u8>, DataError> {
+    let mut decoder = ZlibDecoder::new(compressed);
+    let mut uncompressed = Vec::new();
+
+    decoder
+        .read_to_end(&mut uncompressed)
+        .map_err(DataError::Decompress)?;
+
+    Ok(uncompressed)
+}
+
+/// split the first `N` bytes off
+fn split_le<const N: usize>(bytes: &[u8]) -> Result<([u8; N], &[u8]), DataError> {
+    match bytes.split_first_chunk::<N>() {
+        Some((first, rest)) => Ok((*first, rest)),
+        None => Err(DataError::Truncated),
+    }
+}
+
+/// Save `q` in the data directory as model `name`, with `header` in front of it.
+pub fn q_to_bin<S: State>(q: &Q<S>, name: &str, header: &ModelHeader) -> Result<(), DataError> {
+    model_to_path(q, model_name_to_path(name)?, header)
+}
+
+/// Write a model file at `path`, a leading `~` is the home directory.
+pub fn model_to_path<S: State>(
+    q: &Q<S>,
+    path: impl AsRef<Path>,
+    header: &ModelHeader,
+) -> Result<(), DataError> {
+    let bytes = encode_model(q, header)?;
+
+    Ok(fs::write(expand_home(path.as_ref()), bytes)?)
+}
+
+/// Save a model at a path (see `is_path`) or else in the data directory by name.
+pub fn save_model<S: State>(q: &Q<S>, name: &str, header: &ModelHeader) -> Result<(), DataError> {
+    match is_path(name) {
+        true => model_to_path(q, name, header),
+        false => q_to_bin(q, name, header),
+    }
+}
+
+/// does `name` look like a path to a model file, rather than the name of a model?
+pub fn is_path(name: &str) -> bool {
+    name.ends_with(".bin") || name.contains(std::path::MAIN_SEPARATOR) || name.contains('/')
+}
+
+/// Read a model from anything that reads, e.g. a file or a network stream.
+pub fn read_model<S: State>(mut reader: impl Read) -> Result<(ModelHeader, Q<S>), DataError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    decode_model(&bytes)
+}
+
+/// Read the model file at `path`, a leading `~` is the home directory.
+pub fn path_to_model<S: State>(path: impl AsRef<Path>) -> Result<(ModelHeader, Q<S>), DataError> {
+    read_model(File::open(expand_home(path.as_ref()))?)
+}
+
+/// `~/...` relative to the home directory, as a shell would do
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Read a model, `name` can be
+/// - a path to a model file, e.g. `~/models/easy.bin`
+/// - the name of a model in the data directory
+/// - one of the built-in models: `easy`, `medium` or `hard`
+///
+/// in that order.
+pub fn bin_to_model<S: State>(name: &str) -> Result<(ModelHeader, Q<S>), DataError> {
+    // a missing data directory just means there are no saved models
+    bin_to_model_in(get_data_dir().ok().as_deref(), name)
+}
+
+/// Read a model like `bin_to_model`, with the saved models in `dir`.
+fn bin_to_model_in<S: State>(
+    dir: Option<&Path>,
+    name: &str,
+) -> Result<(ModelHeader, Q<S>), DataError> {
+    let path = expand_home(Path::new(name));
+    if is_path(name) && path.is_file() {
+        return path_to_model(path);
+    }
+
+    let saved = dir
+        .map(|dir| dir.join(format!("{}.bin", name)))
+        .filter(|path| path.is_file());
+    if let Some(path) = saved {
+        return path_to_model(path);
+    }
 
-    let mut encoder = ZlibEncoder::new(
-        get_save_file(name.as_str()).expect("Should get save file"),
-        Compression::best(),
-    );
-    encoder.write_all(&serialized).unwrap();
+    match Difficulty::from_name(name) {
+        Some(difficulty) => difficulty.load_model(),
+        None => Err(DataError::NotFound(name.to_string())),
+    }
+}
 
-    encoder.finish().unwrap();
+/// Read the table of a model (see `bin_to_model`), whether it was saved reduced or not.
+pub fn bin_to_q<S: State>(name: &str) -> Result<Q<S>, DataError> {
+    bin_to_model(name).map(|(_, q)| q)
+}
+
+/// Export the visit counts of every state-action pair to a csv file at `path`,
+/// least visited first, to see which states are under-trained.
+pub fn visits_to_csv<S: State>(visits: &Visits<S>, path: &str) -> Result<(), DataError> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["state", "action", "visits"])?;
+
+    let mut pairs: Vec<(S, S::A, u64)> = visits
+        .iter()
+        .flat_map(|(state, actions)| {
+            actions
+                .iter()
+                .map(|(action, count)| (*state, *action, *count))
+        })
+        .collect();
+    pairs.sort_by_key(|(state, action, count)| (*count, *state, *action));
+
+    for (state, action, count) in pairs {
+        wtr.write_record([
+            format!("{:?}", state),
+            format!("{:?}", action),
+            count.to_string(),
+        ])?;
+    }
+
+    wtr.flush()?;
     Ok(())
 }
 
-pub fn bin_to_q<S: State>(name: &str, reduced: bool) -> Option<Q<S>> {
-    let serialized = get_data(name)?;
+/// Write a training checkpoint to the data directory.
+///
+/// The checkpoint is first written to a temporary file, so an interrupted write
+/// never destroys the previous checkpoint.
+pub fn checkpoint_to_bin<S: State>(
+    checkpoint: &Checkpoint<S>,
+    name: &str,
+) -> Result<(), DataError> {
+    let serialized = bincode::serialize(checkpoint).map_err(DataError::Serialize)?;
+
+    let path = checkpoint_name_to_path(name)?;
+    let temp = path.with_extension("ckpt.tmp");
+
+    let mut encoder = ZlibEncoder::new(File::create(&temp)?, Compression::fast());
+    encoder.write_all(&serialized)?;
+    encoder.finish()?;
+
+    Ok(fs::rename(temp, path)?)
+}
 
-    let mut decoder = ZlibDecoder::new(serialized.as_slice());
-    let mut uncompressed: Vec<u8> = Vec::with_capacity(serialized.len());
+/// Read the training checkpoint with this name from the data directory.
+pub fn bin_to_checkpoint<S: State>(name: &str) -> Result<Checkpoint<S>, DataError> {
+    let file = File::open(checkpoint_name_to_path(name)?)?;
+    let uncompressed = decompress(BufReader::new(file))?;
 
-    decoder.read_to_end(&mut uncompressed).unwrap();
+    bincode::deserialize(&uncompressed).map_err(DataError::Deserialize)
+}
 
-    if reduced {
-        let deserialized: HashMap<S, S::A> =
-            bincode::deserialize(&uncompressed).expect("Should deserialize reduced Q");
+/// names of all the training checkpoints in the data directory
+pub fn list_checkpoints() -> Result<Vec<String>, DataError> {
+    list_data_with(&get_data_dir()?, "ckpt")
+}
 
-        Some(optimal_to_q(deserialized))
-    } else {
-        let deserialized: Q<S> =
-            bincode::deserialize(&uncompressed).expect("Should deserialize unreduced Q");
-        Some(deserialized)
+/// The models to pick from: the saved ones first, then the built-in ones that
+/// don't have the same name as a saved model. Also returns the amount of saved models.
+fn menu_models() -> (Vec<String>, usize) {
+    // without a data directory, the built-in models can still be picked
+    let mut models = list_data().unwrap_or_default();
+    let saved = models.len();
+
+    for difficulty in Difficulty::ALL {
+        if !models.iter().any(|m| m == difficulty.name()) {
+            models.push(difficulty.name().to_string());
+        }
     }
+
+    (models, saved)
 }
 
-fn show_selected_model(models: &Vec<String>, selected: usize, new: bool) {
+fn show_selected_model(models: &[String], saved: usize, selected: usize, new: bool) {
     show::clear();
     println!("Select the saved AI model you want to use");
 
+    let style = render::style();
     for (current, model) in models.iter().enumerate() {
+        let builtin = match current >= saved {
+            true => format!(" {}", style.italic("(built-in)")),
+            false => String::new(),
+        };
         if current == selected {
-            println!("- \x1b[7m{}\x1b[0m", model);
+            println!("- {}{}", style.selected(model), builtin);
         } else {
-            println!("- {}", model);
+            println!("- {}{}", model, builtin);
         }
     }
 
     // also show the option to create a new model
     if new {
-        if selected == models.len() {
-            print!("\x1b[7m");
+        let create = style.green(style.italic("create new"));
+        match selected == models.len() {
+            true => println!("{}", style.selected(create)),
+            false => println!("{create}"),
         }
-
-        println!("\x1b[92;3mcreate new\x1b[0m");
     }
 
     println!();
 }
 
-fn ask_model(new: bool) -> Option<String> {
-    let mut models = list_data();
+/// What was picked in the model selection menu, by name, see `pick_model`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pick {
+    /// a saved or built-in model, to load with `bin_to_q`
+    Model(String),
+    New,
+    Cancel,
+}
+
+/// The terminal in raw mode without a cursor, both are restored when it is dropped.
+struct HiddenCursor(RawTerminal<Stdout>);
 
-    let stdin = stdin();
-    let mut stdout = stdout().into_raw_mode().unwrap();
+impl HiddenCursor {
+    fn new() -> io::Result<Self> {
+        let mut stdout = stdout().into_raw_mode()?;
+        write!(stdout, "{}", termion::cursor::Hide)?;
+        stdout.flush()?;
+        Ok(HiddenCursor(stdout))
+    }
+}
+
+impl Drop for HiddenCursor {
+    fn drop(&mut self) {
+        let _ = write!(self.0, "{}", termion::cursor::Show);
+        let _ = self.0.flush();
+    }
+}
+
+impl Deref for HiddenCursor {
+    type Target = RawTerminal<Stdout>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
 
-    write!(stdout, "{}", termion::cursor::Hide).unwrap();
+impl DerefMut for HiddenCursor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
 
-    stdout.flush().unwrap();
+/// Like `select_model`, but the picked model isn't loaded, only its name is returned.
+pub fn pick_model(new: bool) -> Result<Pick, DataError> {
+    let (mut models, mut saved) = menu_models();
+
+    let stdin = stdin();
+    // the cursor comes back on every way out, errors included
+    let mut stdout = HiddenCursor::new()?;
 
     let mut selected: usize = 0;
+    let mut cancelled = false;
 
-    stdout.suspend_raw_mode().unwrap();
-    show_selected_model(&models, selected, new);
-    stdout.activate_raw_mode().unwrap();
+    stdout.suspend_raw_mode()?;
+    show_selected_model(&models, saved, selected, new);
+    stdout.activate_raw_mode()?;
 
     for c in stdin.keys() {
-        {
-            match c.unwrap() {
-                Key::Char('j') | Key::Down => {
-                    selected += 1;
-                    if new {
-                        selected %= models.len() + 1;
-                    } else {
-                        selected %= models.len();
-                    }
-                }
-                Key::Char('k') | Key::Up => {
-                    if new {
-                        selected += models.len();
-                        selected %= models.len() + 1;
-                    } else {
-                        selected += models.len() - 1;
-                        selected %= models.len();
-                    }
-                }
-                Key::Char(' ') | Key::Char('\n') => break,
-                Key::Char('q') => {
-                    stdout.flush().unwrap();
-                    write!(stdout, "{}", termion::cursor::Show).unwrap();
-                    drop(stdout);
-                    exit(0);
-                }
-                Key::Char('d') => {
-                    if new {
-                        stdout.suspend_raw_mode().unwrap();
-                        print!(
-                            "Are you sure you want to delete {}? [y/N]: ",
-                            models[selected]
-                        );
-                        let answer = show::yes_or_no(false);
-                        if answer {
-                            fs::remove_file(model_name_to_path(&models[selected].clone())).unwrap();
-                            models = list_data();
-                        }
-                        stdout.activate_raw_mode().unwrap();
-                    }
+        // the amount of options in the menu
+        let options = models.len() + usize::from(new);
+
+        match c? {
+            Key::Char('j') | Key::Down if options > 0 => {
+                selected = (selected + 1) % options;
+            }
+            Key::Char('k') | Key::Up if options > 0 => {
+                selected = (selected + options - 1) % options;
+            }
+            Key::Char(' ') | Key::Char('\n') => break,
+            Key::Char('q') => {
+                cancelled = true;
+                break;
+            }
+            // only saved models can be deleted
+            Key::Char('d') if new && selected < saved => {
+                stdout.suspend_raw_mode()?;
+                print!(
+                    "Are you sure you want to delete {}? [y/N]: ",
+                    models[selected]
+                );
+                let answer = show::yes_or_no(false);
+                if answer {
+                    fs::remove_file(model_name_to_path(&models[selected])?)?;
+                    (models, saved) = menu_models();
+                    selected = selected.min(models.len());
                 }
-                _ => {}
+                stdout.activate_raw_mode()?;
             }
+            _ => {}
         }
-        stdout.flush().unwrap();
-        stdout.suspend_raw_mode().unwrap();
+        stdout.flush()?;
+        stdout.suspend_raw_mode()?;
 
         // show current selected
-        show_selected_model(&models, selected, new);
+        show_selected_model(&models, saved, selected, new);
 
-        stdout.activate_raw_mode().unwrap();
+        stdout.activate_raw_mode()?;
     }
+    drop(stdout);
 
-    write!(stdout, "{}", termion::cursor::Show).unwrap();
-
-    if selected < models.len() {
-        Some(models[selected].clone())
+    if cancelled {
+        Ok(Pick::Cancel)
+    } else if selected < models.len() {
+        Ok(Pick::Model(models[selected].clone()))
     } else {
-        None
+        Ok(Pick::New)
     }
 }
 
 /// Use terminal inputs to select one of the available models
 ///
 /// - `new`: set to true if you want to allow the option of creating a new model!
-pub fn select_model<S: State>(new: bool) -> Option<Q<S>> {
-    let model = ask_model(new)?;
+pub fn select_model<S: State>(new: bool) -> Result<Selection<S>, DataError> {
+    match pick_model(new)? {
+        Pick::Model(name) => bin_to_q(&name).map(Selection::Model),
+        Pick::New => Ok(Selection::New),
+        Pick::Cancel => Ok(Selection::Cancel),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::testing::table;
+    use crate::gamestate::GameState;
 
-    bin_to_q(model.as_str(), false)
+    fn compress(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn model_round_trip() {
+        let header = ModelHeader::new::<GameState>(false).with_score(3.5);
+        let bytes = encode_model(&table(), &header).unwrap();
+
+        let (read, q) = decode_model::<GameState>(&bytes).unwrap();
+        assert_eq!(read, header);
+        assert_eq!(q, table());
+    }
+
+    #[test]
+    fn reduced_model_keeps_best_action() {
+        let bytes = encode_model(&table(), &ModelHeader::new::<GameState>(true)).unwrap();
+
+        let (header, q) = decode_model::<GameState>(&bytes).unwrap();
+        assert!(header.reduced);
+        assert_eq!(q, optimal_to_q(q_to_optimal(&table())));
+    }
+
+    #[test]
+    fn legacy_models_are_detected() {
+        let full = compress(&bincode::serialize(&table()).unwrap());
+        let reduced = compress(&bincode::serialize(&q_to_optimal(&table())).unwrap());
+
+        let (header, q) = decode_model::<GameState>(&full).unwrap();
+        assert!(!header.reduced && header.hyperparameters.is_none());
+        assert_eq!(q, table());
+
+        let (header, _) = decode_model::<GameState>(&reduced).unwrap();
+        assert!(header.reduced);
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let mut bytes = encode_model(&table(), &ModelHeader::new::<GameState>(false)).unwrap();
+        bytes[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+
+        let err = decode_model::<GameState>(&bytes).unwrap_err();
+        assert!(matches!(err, DataError::Version { found: 2, .. }));
+        assert!(decode_model::<GameState>(&bytes[..MAGIC.len() + 6]).is_err());
+    }
+
+    #[test]
+    fn truncated_headers_are_refused() {
+        let bytes = encode_model(&table(), &ModelHeader::new::<GameState>(false)).unwrap();
+
+        assert!(matches!(
+            decode_model::<GameState>(&bytes[..MAGIC.len() + 6]),
+            Err(DataError::Truncated)
+        ));
+        assert!(matches!(
+            decode_model::<GameState>(&bytes[..MAGIC.len() + 20]),
+            Err(DataError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn models_are_found_by_path_name_or_difficulty() {
+        let dir = env::temp_dir().join(format!("whister-data-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let q_in_dir = |name| bin_to_model_in::<GameState>(Some(&dir), name).map(|(_, q)| q);
+
+        let path = dir.join("test.bin");
+        model_to_path(&table(), &path, &ModelHeader::new::<GameState>(false)).unwrap();
+        assert_eq!(
+            list_data_with(&dir, "bin").unwrap(),
+            vec![String::from("test")]
+        );
+
+        let by_path = q_in_dir(path.to_str().unwrap()).unwrap();
+        let by_name = q_in_dir("test").unwrap();
+        let by_reader = read_model::<GameState>(File::open(&path).unwrap())
+            .unwrap()
+            .1;
+        assert!(by_path == table() && by_name == table() && by_reader == table());
+
+        assert!(!q_in_dir("easy").unwrap().is_empty());
+        assert!(matches!(q_in_dir("nothing"), Err(DataError::NotFound(_))));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,224 @@
+/*!
+Turns the steps an agent takes in an episode (e.g. one deal) into learning transitions,
+using one-step, n-step or full Monte Carlo returns.
+*/
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+use super::State;
+
+/// What the value of a state-action pair is learned from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Target {
+    /// the next reward, bootstrapped with the value of the next state
+    #[default]
+    OneStep,
+    /// the next `n` rewards, bootstrapped with the value of the state `n` steps later
+    NStep(usize),
+    /// all rewards until the end of the episode, without bootstrapping
+    MonteCarlo,
+}
+
+impl Target {
+    /// amount of rewards before bootstrapping, `None` means never bootstrap
+    fn horizon(&self) -> Option<usize> {
+        match self {
+            Target::OneStep => Some(1),
+            Target::NStep(n) => Some((*n).max(1)),
+            Target::MonteCarlo => None,
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::OneStep => write!(f, "one-step"),
+            Target::NStep(n) => write!(f, "{n}-step"),
+            Target::MonteCarlo => write!(f, "monte-carlo"),
+        }
+    }
+}
+
+/// The state a transition bootstraps from, with what is needed to estimate its value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bootstrap<S: State> {
+    pub state: S,
+    /// the action the behaviour policy takes next in this state (used by SARSA)
+    pub action: S::A,
+    /// all alowed actions in this state (used by Expected SARSA)
+    pub actions: Vec<S::A>,
+}
+
+/// One update for the Q table: `Q(state, action)` should move towards
+/// `reward + discount * V(bootstrap)`, or just `reward` when there is nothing to bootstrap from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transition<S: State> {
+    pub state: S,
+    pub action: S::A,
+    /// the (discounted) sum of the rewards that were collected
+    pub reward: f64,
+    /// the state to bootstrap from, `None` if the episode has ended
+    pub bootstrap: Option<Bootstrap<S>>,
+    /// discount applied to the value of the bootstrap state
+    pub discount: f64,
+}
+
+/// (state, action, reward) and the bootstrap information of the state that followed
+type Step<S> = (S, <S as State>::A, f64, Option<Bootstrap<S>>);
+
+/// Keeps track of the steps in the current episode that haven't been learned from yet.
+pub struct Episode<S: State> {
+    steps: VecDeque<Step<S>>,
+    target: Target,
+    discount: f64,
+}
+
+impl<S: State> Episode<S> {
+    pub fn new(target: Target, discount: f64) -> Self {
+        Episode {
+            steps: VecDeque::new(),
+            target,
+            discount,
+        }
+    }
+
+    /// discount the steps from now on, e.g. when the discount follows a schedule
+    pub fn set_discount(&mut self, discount: f64) {
+        self.discount = discount;
+    }
+
+    /// discounted sum of the rewards of the first `amount` pending steps
+    fn discounted_rewards(&self, amount: usize) -> f64 {
+        self.steps
+            .iter()
+            .take(amount)
+            .enumerate()
+            .map(|(k, (_, _, reward, _))| self.discount.powi(k as i32) * reward)
+            .sum()
+    }
+
+    fn pop_transition(&mut self, amount: usize, terminal: bool) -> Option<Transition<S>> {
+        let reward = self.discounted_rewards(amount);
+        let bootstrap = match terminal {
+            true => None,
+            false => self.steps.get(amount - 1)?.3.clone(),
+        };
+        let (state, action, _, _) = self.steps.pop_front()?;
+
+        Some(Transition {
+            state,
+            action,
+            reward,
+            bootstrap,
+            discount: self.discount.powi(amount as i32),
+        })
+    }
+
+    /// Record that `action` was taken in `state`, which gave `reward` and lead to `next`.
+    /// `next` is ignored when the episode ended (`terminal`).
+    ///
+    /// Returns the transitions that are complete and can be learned from.
+    pub fn step(
+        &mut self,
+        state: S,
+        action: S::A,
+        reward: f64,
+        next: Bootstrap<S>,
+        terminal: bool,
+    ) -> Vec<Transition<S>> {
+        let next = if terminal { None } else { Some(next) };
+        self.steps.push_back((state, action, reward, next));
+
+        let mut transitions = Vec::new();
+
+        if terminal {
+            // nothing to bootstrap from anymore, every pending step gets its full return
+            while !self.steps.is_empty() {
+                let amount = self.steps.len();
+                transitions.extend(self.pop_transition(amount, true));
+            }
+        } else if let Some(horizon) = self.target.horizon() {
+            if self.steps.len() >= horizon {
+                transitions.extend(self.pop_transition(horizon, false));
+            }
+        }
+
+        transitions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::testing::state;
+    use crate::gamestate::{Action, GameState};
+    use crate::suit::Suit;
+
+    const ACTION: Action = Action::PlayWorst(Suit::Clubs);
+
+    fn next(nb_cards: u8) -> Bootstrap<GameState> {
+        Bootstrap {
+            state: state(nb_cards),
+            action: ACTION,
+            actions: vec![ACTION],
+        }
+    }
+
+    #[test]
+    fn one_step_bootstraps_next_state() {
+        let mut episode = Episode::new(Target::OneStep, 0.5);
+
+        let transitions = episode.step(state(0), ACTION, 1.0, next(1), false);
+
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].reward, 1.0);
+        assert_eq!(transitions[0].bootstrap, Some(next(1)));
+        assert_eq!(transitions[0].discount, 0.5);
+    }
+
+    #[test]
+    fn terminal_does_not_bootstrap() {
+        let mut episode = Episode::new(Target::OneStep, 0.5);
+
+        let transitions = episode.step(state(0), ACTION, 1.0, next(1), true);
+
+        assert_eq!(transitions[0].bootstrap, None);
+    }
+
+    #[test]
+    fn n_step_waits_for_enough_rewards() {
+        let mut episode = Episode::new(Target::NStep(2), 0.5);
+
+        assert!(episode
+            .step(state(0), ACTION, 1.0, next(1), false)
+            .is_empty());
+
+        let transitions = episode.step(state(1), ACTION, 1.0, next(2), false);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].state, state(0));
+        assert_eq!(transitions[0].reward, 1.5);
+        assert_eq!(transitions[0].bootstrap, Some(next(2)));
+        assert_eq!(transitions[0].discount, 0.25);
+    }
+
+    #[test]
+    fn monte_carlo_returns_at_end() {
+        let mut episode = Episode::new(Target::MonteCarlo, 0.5);
+
+        assert!(episode
+            .step(state(0), ACTION, 1.0, next(1), false)
+            .is_empty());
+        assert!(episode
+            .step(state(1), ACTION, 0.0, next(2), false)
+            .is_empty());
+
+        let transitions = episode.step(state(2), ACTION, 1.0, next(3), true);
+        let returns: Vec<f64> = transitions.iter().map(|t| t.reward).collect();
+
+        assert_eq!(returns, vec![1.25, 0.5, 1.0]);
+        assert!(transitions.iter().all(|t| t.bootstrap.is_none()));
+    }
+}
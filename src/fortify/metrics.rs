@@ -0,0 +1,222 @@
+/*!
+Periodic measurements of a training in progress, to plot learning curves and spot divergence.
+
+Metrics are enabled with `QLearner::enable_metrics`, and written as csv or json lines
+(depending on the extension of the file) every time a number of iterations is done.
+*/
+
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use super::{State, Q};
+
+/// Scores a Q table, e.g. by playing a fixed set of deals against a rule based opponent.
+pub type Evaluator<S> = Box<dyn Fn(&Q<S>) -> f64 + Send + Sync>;
+
+/// One measurement of the training.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+    pub iteration: u64,
+    /// time since the training (or its resumption) started
+    pub seconds: f64,
+    /// amount of states that have been discovered
+    pub states: usize,
+    /// amount of state-action pairs that have a value
+    pub state_actions: usize,
+    /// mean absolute temporal difference error of the updates since the last measurement
+    pub td_error: f64,
+    pub q_mean: f64,
+    pub q_std: f64,
+    pub q_min: f64,
+    pub q_max: f64,
+    /// epsilon or temperature of the behaviour policy, if it has one
+    pub exploration: Option<f64>,
+    /// score given by the evaluator, if there is one
+    pub score: Option<f64>,
+}
+
+impl Metrics {
+    /// measure the values in `q`, the other fields are left empty
+    pub fn of_table<S: State>(q: &Q<S>) -> Self {
+        let values: Vec<f64> = q.values().flat_map(|m| m.values().copied()).collect();
+
+        let n = values.len().max(1) as f64;
+        let q_mean = values.iter().sum::<f64>() / n;
+        let q_std = (values.iter().map(|v| (v - q_mean).powi(2)).sum::<f64>() / n).sqrt();
+
+        Metrics {
+            iteration: 0,
+            seconds: 0.0,
+            states: q.len(),
+            state_actions: values.len(),
+            td_error: 0.0,
+            q_mean,
+            q_std,
+            q_min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            q_max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            exploration: None,
+            score: None,
+        }
+    }
+}
+
+enum Writer {
+    Csv(Box<csv::Writer<File>>),
+    Jsonl(BufWriter<File>),
+}
+
+/// Keeps track of the temporal difference errors, and writes the metrics to a file.
+pub struct MetricsLog<S: State> {
+    path: PathBuf,
+    writer: Writer,
+    /// write the metrics every this many iterations
+    pub every: u64,
+    evaluate: Option<Evaluator<S>>,
+    /// iteration of the last measurement
+    last: u64,
+    td_sum: f64,
+    td_count: u64,
+    start: Instant,
+}
+
+impl<S: State> MetricsLog<S> {
+    /// Log to `path`, a `.jsonl` file gets json lines, anything else csv.
+    /// An existing log is appended to, e.g. when a training is resumed.
+    pub fn open(
+        path: impl AsRef<Path>,
+        every: u64,
+        evaluate: Option<Evaluator<S>>,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let existing = std::fs::metadata(&path).is_ok_and(|m| m.len() > 0);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        let writer = match path.extension().is_some_and(|e| e == "jsonl") {
+            true => Writer::Jsonl(BufWriter::new(file)),
+            false => Writer::Csv(Box::new(
+                csv::WriterBuilder::new()
+                    .has_headers(!existing)
+                    .from_writer(file),
+            )),
+        };
+
+        Ok(MetricsLog {
+            path,
+            writer,
+            every: every.max(1),
+            evaluate,
+            last: 0,
+            td_sum: 0.0,
+            td_count: 0,
+            start: Instant::now(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// start counting from this iteration, e.g. when the training starts
+    pub fn start_at(&mut self, iteration: u64) {
+        self.last = iteration;
+        self.start = Instant::now();
+    }
+
+    /// remember the temporal difference error of one update
+    pub fn add_td_error(&mut self, error: f64) {
+        self.td_sum += error.abs();
+        self.td_count += 1;
+    }
+
+    /// remember the temporal difference errors of many updates, by their absolute sum
+    pub fn add_td_errors(&mut self, sum: f64, count: u64) {
+        self.td_sum += sum;
+        self.td_count += count;
+    }
+
+    /// should the metrics be written at this iteration?
+    pub fn due(&self, iteration: u64) -> bool {
+        iteration >= self.last + self.every
+    }
+
+    /// Measure `q` at `iteration` and write the metrics.
+    pub fn record(
+        &mut self,
+        q: &Q<S>,
+        iteration: u64,
+        exploration: Option<f64>,
+    ) -> io::Result<Metrics> {
+        let metrics = Metrics {
+            iteration,
+            seconds: self.start.elapsed().as_secs_f64(),
+            td_error: self.td_sum / self.td_count.max(1) as f64,
+            exploration,
+            score: self.evaluate.as_ref().map(|evaluate| evaluate(q)),
+            ..Metrics::of_table(q)
+        };
+
+        match &mut self.writer {
+            Writer::Csv(writer) => {
+                writer.serialize(&metrics).map_err(io::Error::other)?;
+                writer.flush()?;
+            }
+            Writer::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, &metrics).map_err(io::Error::other)?;
+                writeln!(writer)?;
+                writer.flush()?;
+            }
+        }
+
+        self.last = iteration;
+        self.td_sum = 0.0;
+        self.td_count = 0;
+
+        Ok(metrics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::testing::table;
+    use crate::gamestate::GameState;
+
+    #[test]
+    fn table_statistics() {
+        let metrics = Metrics::of_table(&table());
+
+        assert_eq!(metrics.states, 1);
+        assert_eq!(metrics.state_actions, 2);
+        assert_eq!(metrics.q_mean, 2.0);
+        assert_eq!(metrics.q_std, 1.0);
+        assert_eq!((metrics.q_min, metrics.q_max), (1.0, 3.0));
+    }
+
+    #[test]
+    fn log_appends_csv() {
+        let path = std::env::temp_dir().join(format!("whister-metrics-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        for _ in 0..2 {
+            let mut log =
+                MetricsLog::open(&path, 10, Some(Box::new(|_: &Q<GameState>| 0.5))).unwrap();
+            log.add_td_error(-0.2);
+            log.add_td_error(0.4);
+
+            assert!(log.due(10));
+            let metrics = log.record(&table(), 10, None).unwrap();
+            assert!((metrics.td_error - 0.3).abs() < 1e-9);
+            assert!(!log.due(15));
+        }
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // one header, two measurements
+        assert_eq!(contents.lines().count(), 3);
+        assert!(contents.starts_with("iteration,"));
+    }
+}
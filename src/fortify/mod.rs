@@ -17,6 +17,12 @@ learner = QLearner::new_with_iter(1000000);
 // you need to explicitely enable self-play
 learner.enable_self_play();
 
+// explore epsilon-greedy instead of purely at random
+learner.policy = fortify::Policy::EpsilonGreedy {
+    epsilon: 0.3,
+    schedule: fortify::Schedule::Exponential { decay: 0.999999, min: 0.05 },
+};
+
 learner.train(&mut Game::new());
 
 let q = learner.get_q();
@@ -26,15 +32,44 @@ println!("resulting q values: {q:?}");
 */
 
 use indicatif::{ProgressBar, ProgressStyle};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 use std::hash::Hash;
-use std::sync::{mpsc, Arc, RwLock};
+use std::mem;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
+use std::time::{Duration, Instant};
 
+pub mod algorithm;
+pub mod bundled;
+pub mod checkpoint;
 pub mod data;
+pub mod episode;
+pub mod metrics;
+pub mod model;
+pub mod multi;
+pub mod policy;
+pub mod replay;
+pub mod schedule;
+pub mod table;
+pub mod tune;
+
+pub use algorithm::Algorithm;
+pub use bundled::Difficulty;
+pub use checkpoint::{Checkpoint, CheckpointConfig};
+pub use episode::{Bootstrap, Episode, Target, Transition};
+pub use metrics::{Evaluator, Metrics, MetricsLog};
+pub use model::ModelHeader;
+pub use multi::{MultiAgentSpace, TableSharing};
+pub use policy::Policy;
+pub use replay::{Replay, ReplayBuffer, Sample, Sampling};
+pub use schedule::{RateDecay, Schedule, ScheduleKind};
+pub use table::SharedQ;
 
 pub trait State:
     PartialEq
@@ -88,10 +123,38 @@ pub trait GameSpace<S: State>: Send + Sync {
 
     /// performs the action
     fn take_action(&mut self, action: &S::A, q: &Option<&Q<S>>);
+
+    /// has the current episode ended? No actions can be taken in a terminal state.
+    fn is_terminal(&self) -> bool {
+        false
+    }
+
+    /// start a new episode, after the current one has ended
+    fn reset(&mut self, _q: &Option<&Q<S>>) {}
 }
 
 pub type Q<S> = HashMap<S, HashMap<<S as State>::A, f64>>;
 
+/// How many times each state-action pair has been updated during training.
+pub type Visits<S> = HashMap<S, HashMap<<S as State>::A, u64>>;
+
+/// all state-action pairs that have been updated less than `min` times, least visited first
+pub fn under_trained<S: State>(visits: &Visits<S>, min: u64) -> Vec<(S, S::A, u64)> {
+    let mut rare: Vec<(S, S::A, u64)> = visits
+        .iter()
+        .flat_map(|(state, actions)| {
+            actions
+                .iter()
+                .filter(|(_, count)| **count < min)
+                .map(|(action, count)| (*state, *action, *count))
+        })
+        .collect();
+
+    rare.sort_by_key(|(state, action, count)| (*count, *state, *action));
+
+    rare
+}
+
 /// determine the best action in current state, based on the q function
 pub fn best_action_score<S: State>(q: &Q<S>, state: &S) -> Result<(S::A, f64), String> {
     let entry = q.get(state);
@@ -108,21 +171,248 @@ pub fn best_action_score<S: State>(q: &Q<S>, state: &S) -> Result<(S::A, f64), S
     Err("There was no entry for this state.".to_string())
 }
 
+/// the values of the given actions in this state, `initial` is used for unknown ones
+pub fn action_values<S: State>(q: &Q<S>, state: &S, actions: &[S::A], initial: f64) -> Vec<f64> {
+    let entry = q.get(state);
+
+    actions
+        .iter()
+        .map(|action| {
+            entry
+                .and_then(|m| m.get(action))
+                .copied()
+                .unwrap_or(initial)
+        })
+        .collect()
+}
+
+/// The settings a model was trained with, stored in its header for later reference.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Hyperparameters {
+    pub rate: f64,
+    pub rate_schedule: Schedule,
+    pub rate_decay: RateDecay,
+    pub discount: f64,
+    pub discount_schedule: Schedule,
+    pub initial_value: f64,
+    pub queue_size: usize,
+    pub self_play: bool,
+    pub policy: Policy,
+    pub target: Target,
+    pub algorithm: Algorithm,
+    pub replay: Option<Replay>,
+    pub iterations: u64,
+}
+
+impl fmt::Display for Hyperparameters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "rate = {}", self.rate)?;
+        writeln!(f, "rate_schedule = {}", self.rate_schedule)?;
+        writeln!(f, "rate_decay = {}", self.rate_decay)?;
+        writeln!(f, "discount = {}", self.discount)?;
+        writeln!(f, "discount_schedule = {}", self.discount_schedule)?;
+        writeln!(f, "initial_value = {}", self.initial_value)?;
+        writeln!(f, "queue_size = {}", self.queue_size)?;
+        writeln!(f, "self_play = {}", self.self_play)?;
+        writeln!(f, "policy = {}", self.policy)?;
+        writeln!(f, "target = {}", self.target)?;
+        writeln!(f, "algorithm = {}", self.algorithm)?;
+        match &self.replay {
+            Some(replay) => writeln!(f, "replay = {replay}")?,
+            None => writeln!(f, "replay = none")?,
+        }
+        writeln!(f, "iterations = {}", self.iterations)
+    }
+}
+
 pub struct QLearner<S>
 where
     S: State,
 {
     q: Q<S>,
+    /// second table, only used by double Q-learning
+    q_b: Q<S>,
+    /// amount of updates of every state-action pair
+    visits: Visits<S>,
+    /// the table of every seat, after multi-agent training with separate tables
+    seat_tables: Vec<Q<S>>,
     pub rate: f64,
+    /// how the global learning rate changes over the iterations
+    pub rate_schedule: Schedule,
+    /// how the learning rate of a state-action pair decays with its visits
+    pub rate_decay: RateDecay,
     pub discount: f64,
+    /// how the discount changes over the iterations
+    pub discount_schedule: Schedule,
     pub initial_value: f64,
     iterations: u64,
     current_iter: u64,
     self_play: bool,
     pub queue_size: usize,
+    /// the behaviour policy used to explore during training
+    pub policy: Policy,
+    /// what the values are learned from: one-step, n-step or Monte Carlo returns
+    pub target: Target,
+    /// the temporal difference algorithm that is used to learn
+    pub algorithm: Algorithm,
+    /// learn from minibatches of a replay buffer instead of from every transition once
+    pub replay: Option<Replay>,
+    /// source of randomness for the learner and the seeds of its workers
+    rng: ChaCha8Rng,
+    checkpoints: Option<CheckpointConfig>,
+    /// periodic measurements of the training
+    metrics: Option<MetricsLog<S>>,
+    /// maximum amount of threads to train with, all cpus by default
+    threads: Option<usize>,
     verbose: bool,
 }
 
+/// A worker's source of transitions, usually an agent in a gamespace of its own.
+/// Every call takes a step at the given iteration, and adds the transitions that are complete
+/// to the batches, one batch for every table that is trained.
+type Collector<S> = Box<dyn FnMut(&mut [Vec<Transition<S>>], u64, &mut ChaCha8Rng) + Send>;
+
+/// the opponents of self-play get a fresh copy of the table every this many batches of all workers
+const OPPONENT_REFRESH: u64 = 10;
+
+/// what a worker needs to know to update the tables
+#[derive(Clone, Copy)]
+struct UpdateSettings {
+    rate: f64,
+    rate_schedule: Schedule,
+    rate_decay: RateDecay,
+    initial_value: f64,
+    policy: Policy,
+    algorithm: Algorithm,
+}
+
+/// the behaviour policy chooses one of `actions` in `state`, based on the learned tables
+#[allow(clippy::too_many_arguments)]
+fn choose_action<S: State, R: Rng>(
+    table: &SharedQ<S>,
+    double: bool,
+    policy: &Policy,
+    state: &S,
+    actions: &[S::A],
+    initial: f64,
+    iteration: u64,
+    rng: &mut R,
+) -> S::A {
+    assert!(!actions.is_empty(), "the gamespace has no actions in {state:?}");
+
+    // don't bother reading the tables when exploring randomly
+    if *policy == Policy::Random {
+        return actions[rng.gen_range(0..actions.len())];
+    }
+
+    let shard = table.read(state);
+    let mut values = action_values(&shard.tables[0], state, actions, initial);
+
+    // double Q-learning acts on the average of both tables
+    if double {
+        let values_b = action_values(&shard.tables[1], state, actions, initial);
+        values
+            .iter_mut()
+            .zip(values_b)
+            .for_each(|(a, b)| *a = (*a + b) / 2.0);
+    }
+
+    let index = policy.choose(&values, iteration, rng);
+    actions[index.expect("there are actions to choose from")]
+}
+
+/// Update the tables with a batch of transitions, the first of which is update `first`.
+/// The learning rate of every transition is scaled by its weight, if it has one.
+///
+/// Returns the absolute temporal difference errors of the transitions.
+fn learn<S: State, R: Rng>(
+    table: &SharedQ<S>,
+    batch: &[Transition<S>],
+    weights: &[f64],
+    first: u64,
+    settings: &UpdateSettings,
+    double: bool,
+    rng: &mut R,
+) -> Vec<f64> {
+    // first the targets, which only need to read the shards of the bootstrap states
+    let mut updates: Vec<(usize, usize, &Transition<S>, usize, f64)> = batch
+        .iter()
+        .enumerate()
+        .map(|(k, transition)| {
+            let iteration = first + k as u64;
+
+            // double Q-learning updates one of both tables, at random
+            let which = usize::from(double && rng.gen());
+
+            // the value Q(s,a) should move towards, terminal states are worth nothing
+            let target = transition.reward
+                + match &transition.bootstrap {
+                    Some(next) => {
+                        let shard = table.read(&next.state);
+                        transition.discount
+                            * settings.algorithm.bootstrap_value(
+                                &shard.tables[which],
+                                &shard.tables[1 - which],
+                                next,
+                                &settings.policy,
+                                iteration,
+                                settings.initial_value,
+                            )
+                    }
+                    None => 0.0,
+                };
+
+            (
+                table.shard_of(&transition.state),
+                k,
+                transition,
+                which,
+                target,
+            )
+        })
+        .collect();
+
+    // then the updates, locking every shard only once
+    updates.sort_by_key(|update| update.0);
+
+    let mut td_errors = vec![0.0; batch.len()];
+
+    for group in updates.chunk_by(|a, b| a.0 == b.0) {
+        let mut shard = table.write(group[0].0);
+        let shard = &mut *shard;
+
+        for &(_, k, transition, which, target) in group {
+            let iteration = first + k as u64;
+
+            let visits = shard
+                .visits
+                .entry(transition.state)
+                .or_default()
+                .entry(transition.action)
+                .or_default();
+            *visits += 1;
+
+            let rate = settings.rate_decay.rate(
+                settings.rate_schedule.value(settings.rate, iteration),
+                *visits,
+            ) * weights.get(k).unwrap_or(&1.0);
+
+            // get the old value of Q(s,a) if it is available
+            let value = shard.tables[which]
+                .entry(transition.state)
+                .or_default()
+                .entry(transition.action)
+                .or_insert(settings.initial_value);
+
+            td_errors[k] = (target - *value).abs();
+
+            *value += rate * (target - *value);
+        }
+    }
+
+    td_errors
+}
+
 impl<S> QLearner<S>
 where
     S: State + 'static,
@@ -132,21 +422,165 @@ where
 
         QLearner {
             q,
+            q_b: HashMap::new(),
+            visits: HashMap::new(),
+            seat_tables: Vec::new(),
             rate: 0.05,
+            rate_schedule: Schedule::Constant,
+            rate_decay: RateDecay::Constant,
             discount: 0.2,
+            discount_schedule: Schedule::Constant,
             initial_value: 0.8,
             iterations: 100000,
             current_iter: 0,
             self_play: false,
             queue_size: 500,
+            policy: Policy::Random,
+            target: Target::OneStep,
+            algorithm: Algorithm::QLearning,
+            replay: None,
+            rng: ChaCha8Rng::from_entropy(),
+            checkpoints: None,
+            metrics: None,
+            threads: None,
             verbose: true,
         }
     }
 
+    /// Continue a training from a checkpoint.
+    pub fn from_checkpoint(checkpoint: Checkpoint<S>) -> Self {
+        let mut learner = Self::new();
+
+        learner.apply(&checkpoint.hyperparameters);
+        learner.q = checkpoint.q;
+        learner.q_b = checkpoint.q_b;
+        learner.visits = checkpoint.visits;
+        learner.seat_tables = checkpoint.seat_tables;
+        learner.current_iter = checkpoint.current_iter;
+        learner.iterations = checkpoint.iterations;
+        learner.rng = checkpoint.rng;
+
+        learner
+    }
+
+    /// Continue a training from the checkpoint with this name in the data directory.
+    pub fn resume(name: &str) -> Result<Self, data::DataError> {
+        data::bin_to_checkpoint(name).map(Self::from_checkpoint)
+    }
+
+    /// a snapshot of the current training, to be able to resume it later
+    pub fn checkpoint(&self) -> Checkpoint<S> {
+        Checkpoint {
+            q: self.q.clone(),
+            q_b: self.q_b.clone(),
+            visits: self.visits.clone(),
+            seat_tables: self.seat_tables.clone(),
+            current_iter: self.current_iter,
+            iterations: self.iterations,
+            hyperparameters: self.hyperparameters(),
+            rng: self.rng.clone(),
+        }
+    }
+
+    /// Periodically write a checkpoint called `name` to the data directory while training,
+    /// every `every_iterations` iterations and/or every time `every` has passed.
+    pub fn enable_checkpoints(
+        &mut self,
+        name: &str,
+        every_iterations: Option<u64>,
+        every: Option<Duration>,
+    ) {
+        self.checkpoints = Some(CheckpointConfig {
+            name: name.to_string(),
+            every_iterations,
+            every,
+        });
+    }
+
+    /// Write training metrics to `path` (csv, or json lines for `.jsonl`) every `every`
+    /// iterations. The `evaluate` function scores the learned table at every measurement.
+    pub fn enable_metrics(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+        every: u64,
+        evaluate: Option<Evaluator<S>>,
+    ) -> std::io::Result<()> {
+        self.metrics = Some(MetricsLog::open(path, every, evaluate)?);
+        Ok(())
+    }
+
+    /// Seed the learner, to make its exploration reproducible.
+    ///
+    /// *note: the gamespaces use their own randomness, e.g. for dealing cards*
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+    }
+
+    /// set the total amount of iterations, e.g. to train a resumed learner for longer
+    pub fn set_iterations(&mut self, iterations: u64) {
+        self.iterations = iterations;
+    }
+
+    /// the total amount of iterations the training should reach
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// the amount of iterations that have been done so far
+    pub fn current_iteration(&self) -> u64 {
+        self.current_iter
+    }
+
+    /// use the given hyperparameters (except the amount of iterations)
+    pub fn apply(&mut self, hyperparameters: &Hyperparameters) {
+        self.rate = hyperparameters.rate;
+        self.rate_schedule = hyperparameters.rate_schedule;
+        self.rate_decay = hyperparameters.rate_decay;
+        self.discount = hyperparameters.discount;
+        self.discount_schedule = hyperparameters.discount_schedule;
+        self.initial_value = hyperparameters.initial_value;
+        self.queue_size = hyperparameters.queue_size;
+        self.self_play = hyperparameters.self_play;
+        self.policy = hyperparameters.policy;
+        self.target = hyperparameters.target;
+        self.algorithm = hyperparameters.algorithm;
+        self.replay = hyperparameters.replay;
+    }
+
     pub fn enable_self_play(&mut self) {
         self.self_play = true;
     }
 
+    /// let the exploration of the behaviour policy follow `schedule` over the iterations
+    pub fn set_exploration_schedule(&mut self, schedule: Schedule) {
+        self.policy = self.policy.with_schedule(schedule);
+    }
+
+    /// the hyperparameters of this learner, e.g. to save as model metadata
+    pub fn hyperparameters(&self) -> Hyperparameters {
+        Hyperparameters {
+            rate: self.rate,
+            rate_schedule: self.rate_schedule,
+            rate_decay: self.rate_decay,
+            discount: self.discount,
+            discount_schedule: self.discount_schedule,
+            initial_value: self.initial_value,
+            queue_size: self.queue_size,
+            self_play: self.self_play,
+            policy: self.policy,
+            target: self.target,
+            algorithm: self.algorithm,
+            replay: self.replay,
+            iterations: self.current_iter,
+        }
+    }
+
+    /// Limit the amount of threads used for training,
+    /// e.g. to train multiple learners at the same time.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = Some(threads);
+    }
+
     pub fn disable_verbose(&mut self) {
         self.verbose = false;
     }
@@ -167,144 +601,428 @@ where
     /// The gamespace (`game` parameter) is only an example, other spaces will be instantiated in
     /// the multithreaded training process
     pub fn train(&mut self, game: &mut impl GameSpace<S>) {
-        let pb = ProgressBar::new(self.iterations);
+        if self.current_iter >= self.iterations {
+            return;
+        }
+
+        let double = self.algorithm.is_double();
+
+        // double Q-learning starts both tables from the given model
+        if double && self.q_b.is_empty() {
+            self.q_b = self.q.clone();
+        }
+
+        let table = Arc::new(SharedQ::new(
+            [mem::take(&mut self.q), mem::take(&mut self.q_b)],
+            mem::take(&mut self.visits),
+            self.shards(),
+        ));
+
+        // self-playing opponents use a copy of the first table, which is refreshed now and then
+        let opponents = Arc::new(RwLock::new(Arc::new(match self.self_play {
+            true => table.table(0),
+            false => Q::new(),
+        })));
+        let refresh_every = (self.workers() * self.queue_size) as u64 * OPPONENT_REFRESH;
+
+        let settings = self.update_settings();
+        let self_play = self.self_play;
+        let target = self.target;
+        let discount = self.discount;
+        let discount_schedule = self.discount_schedule;
+
+        // every worker plays in its own space
+        let worker_table = Arc::clone(&table);
+        let worker_opponents = Arc::clone(&opponents);
+        let collector = move || -> Collector<S> {
+            let mut local_game = game.new_space();
+            let table = Arc::clone(&worker_table);
+            let opponents = Arc::clone(&worker_opponents);
+            let mut episode = Episode::new(target, discount);
+            let mut opponent_q = opponents.read().unwrap().clone();
+            // the action is chosen one step ahead, SARSA needs to know it
+            let mut next: Option<(S, S::A)> = None;
+
+            Box::new(move |batches, iteration, rng| {
+                let (state, action) = next.take().unwrap_or_else(|| {
+                    let state = local_game.state();
+                    let actions = local_game.actions();
+                    let action = choose_action(
+                        &table,
+                        double,
+                        &settings.policy,
+                        &state,
+                        &actions,
+                        settings.initial_value,
+                        iteration,
+                        rng,
+                    );
+                    (state, action)
+                });
+
+                let opponent = self_play.then_some(&*opponent_q);
+                local_game.take_action(&action, &opponent);
+
+                // reward is the reward that's coupled with this action
+                let reward = local_game.reward();
+                let terminal = local_game.is_terminal();
+
+                if terminal {
+                    local_game.reset(&opponent);
+                }
+
+                // the next state, which starts a new episode if this one has ended
+                let next_state = local_game.state();
+                let next_actions = local_game.actions();
+                let next_action = choose_action(
+                    &table,
+                    double,
+                    &settings.policy,
+                    &next_state,
+                    &next_actions,
+                    settings.initial_value,
+                    iteration,
+                    rng,
+                );
+                next = Some((next_state, next_action));
+
+                let bootstrap = Bootstrap {
+                    state: next_state,
+                    action: next_action,
+                    actions: next_actions,
+                };
+                episode.set_discount(discount_schedule.value(discount, iteration));
+                batches[0].extend(episode.step(state, action, reward, bootstrap, terminal));
+
+                // the new episode is played against the newest opponents
+                if self_play && terminal {
+                    opponent_q = opponents.read().unwrap().clone();
+                }
+            })
+        };
+
+        let mut last_refresh = self.current_iter;
+        let refresh_table = Arc::clone(&table);
+        let monitor = move |current: u64| {
+            if self_play && current - last_refresh >= refresh_every {
+                *opponents.write().unwrap() = Arc::new(refresh_table.table(0));
+                last_refresh = current;
+            }
+        };
+
+        self.run_workers(vec![table], collector, monitor);
+
+        if self.verbose {
+            println!("{} states have been discovered", self.get_q().keys().len());
+            println!("exploration policy: {}", self.policy);
+            println!("learning target: {}", self.target);
+            println!("algorithm: {}", self.algorithm);
+            println!();
+        }
+    }
+
+    /// every worker plays and learns on a thread of its own, all cpus by default
+    fn workers(&self) -> usize {
+        self.threads.unwrap_or_else(num_cpus::get).max(1)
+    }
+
+    /// plenty of shards, so workers rarely need the same one
+    fn shards(&self) -> usize {
+        self.workers() * 16
+    }
+
+    fn update_settings(&self) -> UpdateSettings {
+        UpdateSettings {
+            rate: self.rate,
+            rate_schedule: self.rate_schedule,
+            rate_decay: self.rate_decay,
+            initial_value: self.initial_value,
+            policy: self.policy,
+            algorithm: self.algorithm,
+        }
+    }
+
+    /// Train `tables` with the transitions of the workers, which every worker gathers with
+    /// a collector of its own, made by `collector`.
+    ///
+    /// A worker learns from its transitions, or from its replay buffers, every `queue_size`
+    /// transitions. Meanwhile this thread shows the progress, writes checkpoints and metrics,
+    /// and calls `monitor` with the current iteration.
+    /// Afterwards, the first table is the learner's, see `keep_tables`.
+    fn run_workers(
+        &mut self,
+        tables: Vec<Arc<SharedQ<S>>>,
+        mut collector: impl FnMut() -> Collector<S>,
+        mut monitor: impl FnMut(u64),
+    ) {
+        let pb = match self.verbose {
+            true => ProgressBar::new(self.iterations),
+            false => ProgressBar::hidden(),
+        };
+        // a resumed training doesn't start at zero
+        pb.set_position(self.current_iter);
         pb.set_style(
             ProgressStyle::with_template("  {bar:40.green/black}  {pos} / {len}  eta: {eta}")
                 .unwrap()
                 .progress_chars("━━─"),
         );
 
-        // create the channel
-        let (tx, rx) = mpsc::channel();
+        let workers = self.workers();
+        let double = self.algorithm.is_double();
+        let settings = self.update_settings();
+        let queue_size = self.queue_size.max(1);
+        let iterations = self.iterations;
+        let replay = self.replay;
+        let beta = match self.replay.map(|r| r.sampling) {
+            Some(Sampling::Prioritized { beta, .. }) => beta,
+            _ => 1.0,
+        };
+
+        // the amount of updates that have been claimed by the workers
+        let iteration = Arc::new(AtomicU64::new(self.current_iter));
+        // sum of the absolute temporal difference errors and their amount, for the metrics
+        let td_errors = Arc::new(Mutex::new((0.0, 0)));
 
-        // keep track of the different handles
         let mut handles = Vec::new();
 
-        // create a shared ownership Q
-        let q = Arc::new(RwLock::new(self.q.clone()));
+        for _ in 0..workers {
+            let mut collect = collector();
+            let tables = tables.clone();
+            let iteration = Arc::clone(&iteration);
+            let td_errors = Arc::clone(&td_errors);
 
-        let num_cpu = num_cpus::get();
+            // every worker gets its own generator, seeded by the learner's
+            let mut rng = ChaCha8Rng::seed_from_u64(self.rng.gen());
 
-        let producers = {
-            if num_cpu < 2 {
-                1
-            } else {
-                // use all but one of the available cpus
-                // the last one should be kept free for the consumer
-                num_cpu - 1
-            }
-        };
+            let handle = thread::spawn(move || {
+                let mut batches = vec![Vec::with_capacity(queue_size); tables.len()];
+                // the buffers are split over the workers
+                let mut buffers: Vec<Option<ReplayBuffer<S>>> = tables
+                    .iter()
+                    .map(|_| {
+                        replay.map(|r| {
+                            ReplayBuffer::new((r.capacity / workers).max(queue_size), r.sampling)
+                        })
+                    })
+                    .collect();
 
-        let local_init = self.initial_value;
-        let local_self = self.self_play;
-        let local_disc = self.discount;
+                loop {
+                    collect(
+                        &mut batches,
+                        iteration.load(atomic::Ordering::Relaxed),
+                        &mut rng,
+                    );
 
-        for _ in 0..producers {
-            // clone the tranceiver
-            let local_tx = tx.clone();
-            // create a new space to learn in
-            let mut local_game = game.new_space();
+                    if batches.iter().map(Vec::len).sum::<usize>() < queue_size {
+                        continue;
+                    }
 
-            let q = Arc::clone(&q);
+                    let tables = tables.iter().zip(&mut batches).zip(&mut buffers);
+                    for ((table, batch), buffer) in tables {
+                        // learn from the new transitions, or from minibatches of the replay buffer
+                        let minibatches = match buffer {
+                            Some(buffer) => {
+                                batch.drain(..).for_each(|t| buffer.push(t));
+                                replay.map_or(1, |r| r.ratio)
+                            }
+                            None if batch.is_empty() => 0,
+                            None => 1,
+                        };
 
-            let handle = thread::spawn(move || {
-                loop {
-                    let current_state = local_game.state();
+                        for _ in 0..minibatches {
+                            let (mut transitions, weights, indices) = match buffer {
+                                Some(buffer) => {
+                                    // correct the prioritization bias fully by the end of the training
+                                    let progress = iteration.load(atomic::Ordering::Relaxed)
+                                        as f64
+                                        / iterations as f64;
+                                    buffer.set_beta(beta + (1.0 - beta) * progress.min(1.0));
 
-                    // determine a new action to take, from current state
-                    let action = local_game.random_action();
+                                    let samples = buffer.sample(queue_size, &mut rng);
+                                    let indices = samples.iter().map(|s| s.index).collect();
+                                    let weights = samples.iter().map(|s| s.weight).collect();
+                                    let transitions = samples.into_iter().map(|s| s.transition);
+                                    (transitions.collect(), weights, indices)
+                                }
+                                None => (mem::take(batch), Vec::new(), Vec::new()),
+                            };
 
-                    if local_self {
-                        local_game.take_action(&action, &Some(&q.read().unwrap()));
-                    } else {
-                        local_game.take_action(&action, &None)
-                    }
+                            // claim iterations for the batch, the last batch may only be partly used
+                            let first = iteration
+                                .fetch_add(transitions.len() as u64, atomic::Ordering::Relaxed);
+                            if first >= iterations {
+                                return;
+                            }
+                            transitions.truncate(
+                                (iterations - first).min(transitions.len() as u64) as usize,
+                            );
+
+                            let errors = learn(
+                                table,
+                                &transitions,
+                                &weights,
+                                first,
+                                &settings,
+                                double,
+                                &mut rng,
+                            );
+                            {
+                                let mut td_errors = td_errors.lock().unwrap();
+                                td_errors.0 += errors.iter().sum::<f64>();
+                                td_errors.1 += errors.len() as u64;
+                            }
+
+                            if let Some(buffer) = buffer {
+                                buffer.update_priorities(&indices, &errors);
+                            }
 
-                    // reward is the reward that's coupled with this action
-                    let reward = local_game.reward();
-                    let best_future = {
-                        let best = best_action_score(&q.read().unwrap(), &local_game.state());
-                        if let Ok(best) = best {
-                            best.1
-                        } else {
-                            local_init
+                            if first + transitions.len() as u64 >= iterations {
+                                return;
+                            }
                         }
-                    };
-
-                    // send the values to the consumer
-                    let Ok(_) =
-                        local_tx.send((current_state, action, reward + local_disc * best_future))
-                    else {
-                        break;
-                    };
+                    }
                 }
             });
             handles.push(handle);
         }
+        drop(collector);
 
-        let mut rcv_queue = Vec::new();
-
-        // consumer loop
-        'outer: for rcv in rx {
-            rcv_queue.push(rcv);
-
-            if rcv_queue.len() == producers * self.queue_size {
-                let mut my_q = q.write().unwrap();
+        if let Some(metrics) = &mut self.metrics {
+            metrics.start_at(self.current_iter);
+        }
 
-                // consume the entire queue
-                while let Some(rcv) = rcv_queue.pop() {
-                    let (current_state, action, new) = rcv;
+        // progress since the last checkpoint
+        let mut last_checkpoint = (self.current_iter, Instant::now());
 
-                    // new value to assign to Q(s,a)
-                    let v: f64 = {
-                        // get the old value of Q(s,a) if it is available
-                        let old_value = my_q
-                            .get(&current_state)
-                            .and_then(|m| m.get(&action))
-                            .unwrap_or(&self.initial_value);
+        while !handles.iter().all(|handle| handle.is_finished()) {
+            thread::sleep(Duration::from_millis(20));
 
-                        *old_value + self.rate * (new - *old_value)
-                    };
+            self.current_iter = iteration
+                .load(atomic::Ordering::Relaxed)
+                .min(self.iterations);
+            pb.set_position(self.current_iter);
 
-                    my_q.entry(current_state)
-                        .or_insert_with(HashMap::new)
-                        .insert(action, v);
+            monitor(self.current_iter);
 
-                    self.current_iter += 1;
+            if let Some(config) = &self.checkpoints {
+                let done = self.current_iter - last_checkpoint.0;
+                if config.due(done, last_checkpoint.1.elapsed()) {
+                    self.keep_tables(tables.iter().map(|table| table.snapshot()).collect());
+                    self.write_checkpoint();
+                    last_checkpoint = (self.current_iter, Instant::now());
+                }
+            }
 
-                    if self.verbose {
-                        pb.inc(1);
-                    }
+            if let Some(metrics) = &mut self.metrics {
+                if metrics.due(self.current_iter) {
+                    let (sum, count) = mem::take(&mut *td_errors.lock().unwrap());
+                    metrics.add_td_errors(sum, count);
 
-                    if self.current_iter >= self.iterations {
-                        break 'outer;
-                    }
+                    self.keep_tables(tables.iter().map(|table| table.snapshot()).collect());
+                    self.write_metrics();
                 }
             }
         }
+        drop(monitor);
 
         for handle in handles {
             handle.join().unwrap();
         }
 
-        self.q = q.read().unwrap().clone();
+        // the workers are done, so these are the only references left
+        let parts = tables
+            .into_iter()
+            .map(|table| {
+                let Ok(table) = Arc::try_unwrap(table) else {
+                    unreachable!("the workers should have dropped their tables");
+                };
+                table.into_parts()
+            })
+            .collect();
+        self.keep_tables(parts);
+        self.current_iter = self.iterations;
 
-        if self.verbose {
-            pb.finish();
-            println!(
-                "\x1b[3m{} states\x1b[0m have been discovered",
-                self.q.keys().len()
+        // always leave a checkpoint of the finished training, to be able to extend it
+        if self.checkpoints.is_some() {
+            self.write_checkpoint();
+        }
+
+        if let Some(metrics) = &mut self.metrics {
+            let (sum, count) = mem::take(&mut *td_errors.lock().unwrap());
+            metrics.add_td_errors(sum, count);
+            self.write_metrics();
+        }
+
+        pb.finish();
+    }
+
+    /// Keep the trained tables and visit counts: the first ones are the learner's own.
+    /// With more than one, they are the tables of every seat, see `get_seat_q`.
+    fn keep_tables(&mut self, mut parts: Vec<([Q<S>; 2], Visits<S>)>) {
+        self.seat_tables = match parts.len() {
+            1 => Vec::new(),
+            _ => parts
+                .iter()
+                .map(|([q, q_b], _)| match self.algorithm.is_double() {
+                    true => algorithm::average(q, q_b, self.initial_value),
+                    false => q.clone(),
+                })
+                .collect(),
+        };
+
+        ([self.q, self.q_b], self.visits) = parts.swap_remove(0);
+    }
+
+    fn write_checkpoint(&self) {
+        let Some(config) = &self.checkpoints else {
+            return;
+        };
+
+        // a failed checkpoint shouldn't stop the training
+        if let Err(err) = data::checkpoint_to_bin(&self.checkpoint(), &config.name) {
+            eprintln!("Could not write checkpoint {}: {}", config.name, err);
+        }
+    }
+
+    fn write_metrics(&mut self) {
+        let q = self.get_q();
+        let exploration = self.policy.exploration(self.current_iter);
+        let Some(metrics) = &mut self.metrics else {
+            return;
+        };
+
+        // failing to log shouldn't stop the training either
+        if let Err(err) = metrics.record(&q, self.current_iter, exploration) {
+            eprintln!(
+                "Could not write metrics to {}: {}",
+                metrics.path().display(),
+                err
             );
-            println!();
         }
     }
 
+    /// The learned Q values. For double Q-learning, this is the average of both tables.
     pub fn get_q(&self) -> Q<S> {
-        self.q.clone()
+        if self.algorithm.is_double() {
+            algorithm::average(&self.q, &self.q_b, self.initial_value)
+        } else {
+            self.q.clone()
+        }
     }
 
     pub fn set_q(&mut self, q: Q<S>) {
         self.q = q;
+        self.q_b = HashMap::new();
+        self.seat_tables = Vec::new();
+    }
+
+    /// how many times each state-action pair has been updated
+    pub fn get_visits(&self) -> Visits<S> {
+        self.visits.clone()
+    }
+
+    pub fn set_visits(&mut self, visits: Visits<S>) {
+        self.visits = visits;
     }
 }
 
@@ -0,0 +1,151 @@
+/*!
+The header of a saved model, which tells what is in the file and how it was trained.
+
+A model file starts with the magic bytes `WHISTQ`, followed by the format version
+(a little endian `u32`), the length of the header (a little endian `u64`), the header
+itself (bincode) and finally the zlib compressed table.
+Files without the magic bytes are models from before the header existed,
+`fortify::data` still reads those.
+*/
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::data::DataError;
+use super::{Hyperparameters, State};
+
+/// the first bytes of every model file
+pub const MAGIC: &[u8; 6] = b"WHISTQ";
+
+/// version of the model file format that is written, and the only one that is read
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Everything that is known about a saved model, besides the table itself.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelHeader {
+    /// name of the state type of the table, to refuse tables of other state types
+    pub state: String,
+    /// only the best action of every state is stored
+    pub reduced: bool,
+    /// the hyperparameters the model was trained with, unknown for older models
+    pub hyperparameters: Option<Hyperparameters>,
+    /// amount of iterations the model was trained for
+    pub iterations: u64,
+    /// seconds since the unix epoch, 0 if unknown
+    pub created: u64,
+    /// average tricks per deal against the rule based opponent, if evaluated
+    pub score: Option<f64>,
+}
+
+impl ModelHeader {
+    /// a header for a table of states `S`, created now
+    pub fn new<S: State>(reduced: bool) -> Self {
+        ModelHeader {
+            state: state_name::<S>(),
+            reduced,
+            hyperparameters: None,
+            iterations: 0,
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            score: None,
+        }
+    }
+
+    /// add the hyperparameters (and with them the iteration count) of the training
+    pub fn with_hyperparameters(mut self, hyperparameters: Hyperparameters) -> Self {
+        self.iterations = hyperparameters.iterations;
+        self.hyperparameters = Some(hyperparameters);
+        self
+    }
+
+    pub fn with_score(mut self, score: f64) -> Self {
+        self.score = Some(score);
+        self
+    }
+
+    /// is this a header of a table of states `S`?
+    pub fn check_state<S: State>(&self) -> Result<(), DataError> {
+        let expected = state_name::<S>();
+
+        match self.state == expected {
+            true => Ok(()),
+            false => Err(DataError::StateMismatch {
+                found: self.state.clone(),
+                expected,
+            }),
+        }
+    }
+}
+
+/// the name of a state type, as stored in the header
+pub fn state_name<S: State>() -> String {
+    let name = std::any::type_name::<S>();
+    // without the module path, so moving the type around keeps models readable
+    name.rsplit("::").next().unwrap_or(name).to_string()
+}
+
+/// the date of a unix timestamp, as `yyyy-mm-dd`
+pub fn date(seconds: u64) -> String {
+    // days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+impl fmt::Display for ModelHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "state = {}", self.state)?;
+        writeln!(f, "reduced = {}", self.reduced)?;
+        writeln!(f, "iterations = {}", self.iterations)?;
+        match self.created {
+            0 => writeln!(f, "created = unknown")?,
+            created => writeln!(f, "created = {}", date(created))?,
+        }
+        match self.score {
+            Some(score) => writeln!(f, "score = {score:.4}")?,
+            None => writeln!(f, "score = unknown")?,
+        }
+        match &self.hyperparameters {
+            Some(hyperparameters) => write!(f, "{hyperparameters}"),
+            None => writeln!(f, "hyperparameters = unknown"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::GameState;
+
+    #[test]
+    fn dates_of_timestamps() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951782400), "2000-02-29");
+        assert_eq!(date(1703376000), "2023-12-24");
+    }
+
+    #[test]
+    fn header_knows_its_state() {
+        let header = ModelHeader::new::<GameState>(true);
+
+        assert_eq!(header.state, "GameState");
+        assert!(header.check_state::<GameState>().is_ok());
+
+        let other = ModelHeader {
+            state: String::from("BidState"),
+            ..header
+        };
+        assert!(other.check_state::<GameState>().is_err());
+    }
+}
@@ -0,0 +1,211 @@
+/*!
+Multi-agent environments, in which every seat at the table can be a learner.
+
+A `MultiAgentSpace` tells which seat acts, what every seat observes, and hands out
+rewards per seat. `QLearner::train_multi_agent` trains all seats at the same time,
+either with one table they share or with a table per seat.
+*/
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+use super::{choose_action, Bootstrap, Collector, Episode, QLearner, SharedQ, State, Q};
+
+/// A game in which multiple agents (seats) take turns.
+pub trait MultiAgentSpace<S: State>: Send + Sync {
+    /// returns a new gamespace to learn in
+    fn new_space(&self) -> Box<dyn MultiAgentSpace<S>>;
+
+    /// amount of seats at the table
+    fn seats(&self) -> usize;
+
+    /// the seat that acts next
+    fn to_act(&self) -> usize;
+
+    /// what `seat` observes of the game
+    fn observe(&self, seat: usize) -> S;
+
+    /// alowed actions of the seat that acts next
+    fn actions(&self) -> Vec<S::A>;
+
+    /// The seat to act performs `action`.
+    /// Returns the reward every seat got because of it, indexed by seat.
+    fn step(&mut self, action: &S::A) -> Vec<f64>;
+
+    /// has the current episode ended?
+    fn is_terminal(&self) -> bool;
+
+    /// start a new episode, after the current one has ended
+    fn reset(&mut self);
+}
+
+/// Which table the seats learn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum TableSharing {
+    /// all seats learn (and play with) the same table
+    #[default]
+    Shared,
+    /// every seat has its own table
+    Separate,
+}
+
+impl fmt::Display for TableSharing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableSharing::Shared => write!(f, "shared"),
+            TableSharing::Separate => write!(f, "separate"),
+        }
+    }
+}
+
+/// the step of a seat that waits for the seat's next turn to be learned from
+struct Pending<S: State> {
+    state: S,
+    action: S::A,
+    /// rewards collected since the seat acted
+    reward: f64,
+}
+
+impl<S> QLearner<S>
+where
+    S: State + 'static,
+{
+    /// Train every seat of a multi-agent gamespace at the same time.
+    ///
+    /// A seat learns from its own observations and rewards: the rewards it collects
+    /// until its next turn belong to the action it took.
+    /// With separate tables, `get_seat_q` gives the table of every seat afterwards,
+    /// and `get_q` the one of seat 0, which is also the one the metrics measure.
+    pub fn train_multi_agent(&mut self, game: &impl MultiAgentSpace<S>, sharing: TableSharing) {
+        if self.current_iter >= self.iterations {
+            return;
+        }
+
+        let seats = game.seats();
+        let double = self.algorithm.is_double();
+
+        if double && self.q_b.is_empty() {
+            self.q_b = self.q.clone();
+        }
+
+        // every seat continues from its own table if it has one, and counts its own visits
+        let start = |seat: usize| match self.seat_tables.get(seat) {
+            Some(q) => [q.clone(), if double { q.clone() } else { Q::new() }],
+            None => [self.q.clone(), self.q_b.clone()],
+        };
+        let tables: Vec<Arc<SharedQ<S>>> = match sharing {
+            TableSharing::Shared => vec![[self.q.clone(), self.q_b.clone()]],
+            TableSharing::Separate => (0..seats).map(start).collect(),
+        }
+        .into_iter()
+        .map(|tables| Arc::new(SharedQ::new(tables, self.visits.clone(), self.shards())))
+        .collect();
+
+        // the table a seat learns and plays with
+        let table_of = move |seat: usize| match sharing {
+            TableSharing::Shared => 0,
+            TableSharing::Separate => seat,
+        };
+
+        let settings = self.update_settings();
+        let target = self.target;
+        let discount = self.discount;
+        let discount_schedule = self.discount_schedule;
+
+        let worker_tables = tables.clone();
+        let collector = move || -> Collector<S> {
+            let mut local_game = game.new_space();
+            let tables = worker_tables.clone();
+            let mut episodes: Vec<Episode<S>> =
+                (0..seats).map(|_| Episode::new(target, discount)).collect();
+            let mut pending: Vec<Option<Pending<S>>> = (0..seats).map(|_| None).collect();
+
+            Box::new(move |batches, iteration, rng| {
+                let seat = local_game.to_act();
+                let state = local_game.observe(seat);
+                let actions = local_game.actions();
+                let action = choose_action(
+                    &tables[table_of(seat)],
+                    double,
+                    &settings.policy,
+                    &state,
+                    &actions,
+                    settings.initial_value,
+                    iteration,
+                    rng,
+                );
+
+                let current = discount_schedule.value(discount, iteration);
+                episodes.iter_mut().for_each(|e| e.set_discount(current));
+
+                // the previous step of this seat can bootstrap from where it is now
+                if let Some(step) = pending[seat].take() {
+                    let next = Bootstrap {
+                        state,
+                        action,
+                        actions,
+                    };
+                    batches[table_of(seat)].extend(episodes[seat].step(
+                        step.state,
+                        step.action,
+                        step.reward,
+                        next,
+                        false,
+                    ));
+                }
+
+                let rewards = local_game.step(&action);
+                pending[seat] = Some(Pending {
+                    state,
+                    action,
+                    reward: 0.0,
+                });
+                for (step, reward) in pending.iter_mut().zip(rewards) {
+                    if let Some(step) = step {
+                        step.reward += reward;
+                    }
+                }
+
+                if local_game.is_terminal() {
+                    for (seat, step) in pending.iter_mut().enumerate() {
+                        let Some(step) = step.take() else {
+                            continue;
+                        };
+                        // nothing to bootstrap from at the end
+                        let next = Bootstrap {
+                            state: step.state,
+                            action: step.action,
+                            actions: Vec::new(),
+                        };
+                        batches[table_of(seat)].extend(episodes[seat].step(
+                            step.state,
+                            step.action,
+                            step.reward,
+                            next,
+                            true,
+                        ));
+                    }
+                    local_game.reset();
+                }
+            })
+        };
+
+        self.run_workers(tables, collector, |_| {});
+
+        if self.verbose {
+            println!("{} states have been discovered by seat 0", self.q.len());
+            println!("tables: {sharing}");
+            println!();
+        }
+    }
+
+    /// The learned table of `seat`, after multi-agent training with separate tables.
+    /// Otherwise, this is the same table for every seat.
+    pub fn get_seat_q(&self, seat: usize) -> Q<S> {
+        match self.seat_tables.get(seat) {
+            Some(q) => q.clone(),
+            None => self.get_q(),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+/*!
+Behaviour policies, which determine how the agent picks its actions while it is being trained.
+
+A policy works on the (estimated) values of the alowed actions in the current state,
+and returns the index of the action it wants to take.
+*/
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::schedule::Schedule;
+
+/// The way an agent explores the gamespace during training.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum Policy {
+    /// pick any alowed action, uniformly at random
+    #[default]
+    Random,
+    /// pick a random action with chance epsilon, otherwise the best known one
+    EpsilonGreedy { epsilon: f64, schedule: Schedule },
+    /// pick actions with a chance proportional to `exp(value / temperature)`
+    Softmax {
+        temperature: f64,
+        schedule: Schedule,
+    },
+}
+
+/// index of the highest value, the first one wins in case of ties
+fn greedy(values: &[f64]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .max_by(|(i, x), (j, y)| x.partial_cmp(y).unwrap_or(Ordering::Equal).then(j.cmp(i)))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+impl Policy {
+    /// exploration parameter (epsilon or temperature) at the given iteration
+    pub fn exploration(&self, iteration: u64) -> Option<f64> {
+        match self {
+            Policy::Random => None,
+            Policy::EpsilonGreedy { epsilon, schedule } => {
+                Some(schedule.value(*epsilon, iteration))
+            }
+            Policy::Softmax {
+                temperature,
+                schedule,
+            } => Some(schedule.value(*temperature, iteration)),
+        }
+    }
+
+    /// the same policy, but with its exploration following `schedule`
+    pub fn with_schedule(self, schedule: Schedule) -> Policy {
+        match self {
+            Policy::Random => Policy::Random,
+            Policy::EpsilonGreedy { epsilon, .. } => Policy::EpsilonGreedy { epsilon, schedule },
+            Policy::Softmax { temperature, .. } => Policy::Softmax {
+                temperature,
+                schedule,
+            },
+        }
+    }
+
+    /// The chance of choosing each of the actions with these `values`.
+    pub fn probabilities(&self, values: &[f64], iteration: u64) -> Vec<f64> {
+        let n = values.len();
+        if n == 0 {
+            return Vec::new();
+        }
+
+        match self {
+            Policy::Random => vec![1.0 / n as f64; n],
+            Policy::EpsilonGreedy { .. } => {
+                let epsilon = self.exploration(iteration).unwrap_or(1.0).clamp(0.0, 1.0);
+                let mut probs = vec![epsilon / n as f64; n];
+                probs[greedy(values)] += 1.0 - epsilon;
+                probs
+            }
+            Policy::Softmax { .. } => {
+                let temperature = self.exploration(iteration).unwrap_or(1.0);
+
+                // a (near) zero temperature is just greedy
+                if temperature <= f64::EPSILON {
+                    let mut probs = vec![0.0; n];
+                    probs[greedy(values)] = 1.0;
+                    return probs;
+                }
+
+                // subtract the max for numerical stability
+                let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                let exps: Vec<f64> = values
+                    .iter()
+                    .map(|v| ((v - max) / temperature).exp())
+                    .collect();
+                let total: f64 = exps.iter().sum();
+
+                exps.iter().map(|e| e / total).collect()
+            }
+        }
+    }
+
+    /// Choose one of the actions with these `values`, returns its index,
+    /// or `None` when there are no actions to choose from.
+    pub fn choose<R: Rng + ?Sized>(
+        &self,
+        values: &[f64],
+        iteration: u64,
+        rng: &mut R,
+    ) -> Option<usize> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let index = match self {
+            Policy::Random => rng.gen_range(0..values.len()),
+            Policy::EpsilonGreedy { .. } => {
+                let epsilon = self.exploration(iteration).unwrap_or(1.0);
+                if rng.gen::<f64>() < epsilon {
+                    rng.gen_range(0..values.len())
+                } else {
+                    greedy(values)
+                }
+            }
+            Policy::Softmax { .. } => {
+                let probs = self.probabilities(values, iteration);
+                let mut pick = rng.gen::<f64>();
+
+                for (i, p) in probs.iter().enumerate() {
+                    if pick < *p {
+                        return Some(i);
+                    }
+                    pick -= p;
+                }
+
+                // rounding errors, just take the last one
+                values.len() - 1
+            }
+        };
+        Some(index)
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Policy::Random => write!(f, "random"),
+            Policy::EpsilonGreedy { epsilon, schedule } => {
+                write!(f, "epsilon-greedy(epsilon={epsilon}, schedule={schedule})")
+            }
+            Policy::Softmax {
+                temperature,
+                schedule,
+            } => write!(f, "softmax(temperature={temperature}, schedule={schedule})"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn greedy_without_exploration() {
+        let policy = Policy::EpsilonGreedy {
+            epsilon: 0.0,
+            schedule: Schedule::Constant,
+        };
+        let mut rng = rand::thread_rng();
+
+        assert_eq!(policy.choose(&[0.1, 0.9, 0.3], 0, &mut rng), Some(1));
+    }
+
+    #[test]
+    fn nothing_to_choose_from() {
+        let mut rng = rand::thread_rng();
+
+        assert_eq!(Policy::Random.choose(&[], 0, &mut rng), None);
+    }
+
+    #[test]
+    fn probabilities_sum_to_one() {
+        let values = [0.1, 0.9, 0.3, 0.5];
+        let policies = [
+            Policy::Random,
+            Policy::EpsilonGreedy {
+                epsilon: 0.3,
+                schedule: Schedule::Constant,
+            },
+            Policy::Softmax {
+                temperature: 0.5,
+                schedule: Schedule::Constant,
+            },
+        ];
+
+        for policy in policies {
+            let total: f64 = policy.probabilities(&values, 0).iter().sum();
+            assert!((total - 1.0).abs() < 1e-9, "{policy} does not sum to one");
+        }
+    }
+
+    #[test]
+    fn softmax_prefers_higher_values() {
+        let policy = Policy::Softmax {
+            temperature: 0.1,
+            schedule: Schedule::Constant,
+        };
+        let probs = policy.probabilities(&[0.1, 0.9], 0);
+
+        assert!(probs[1] > probs[0]);
+    }
+}
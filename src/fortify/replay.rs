@@ -0,0 +1,338 @@
+/*!
+Experience replay: transitions are kept in a buffer of fixed capacity and learned from
+multiple times, in random minibatches, instead of once in the order they happened.
+
+Transitions can be sampled uniformly, or prioritized by their last temporal difference error
+(Schaul et al., 2016), with importance sampling weights to correct for the bias that gives.
+The targets of replayed transitions are computed with the current values,
+since a `Transition` only stores the state to bootstrap from.
+*/
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{State, Transition};
+
+/// smallest priority, so transitions without error can still be sampled
+const MIN_PRIORITY: f64 = 1e-4;
+
+/// How transitions are drawn from the buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum Sampling {
+    /// every transition is as likely
+    #[default]
+    Uniform,
+    /// proportional to `|td error| ^ alpha`, corrected with weights `(N * P(i)) ^ -beta`
+    Prioritized { alpha: f64, beta: f64 },
+}
+
+/// Settings of the replay buffer of a learner.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    /// the most transitions the buffer holds, the oldest are replaced first
+    pub capacity: usize,
+    pub sampling: Sampling,
+    /// amount of minibatches learned from for every batch of new transitions
+    pub ratio: usize,
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Replay {
+            capacity: 100000,
+            sampling: Sampling::Uniform,
+            ratio: 1,
+        }
+    }
+}
+
+/// A binary tree in an array, every node holds the sum of its children,
+/// so transitions can be sampled proportional to their priority in logarithmic time.
+#[derive(Clone, Debug)]
+struct SumTree {
+    nodes: Vec<f64>,
+    leaves: usize,
+}
+
+impl SumTree {
+    fn new(leaves: usize) -> Self {
+        let leaves = leaves.max(1).next_power_of_two();
+
+        SumTree {
+            nodes: vec![0.0; 2 * leaves],
+            leaves,
+        }
+    }
+
+    fn total(&self) -> f64 {
+        self.nodes[1]
+    }
+
+    fn get(&self, index: usize) -> f64 {
+        self.nodes[self.leaves + index]
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let mut node = self.leaves + index;
+        self.nodes[node] = value;
+
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = self.nodes[2 * node] + self.nodes[2 * node + 1];
+        }
+    }
+
+    /// the leaf where the running sum of the leaves passes `mass`
+    fn find(&self, mut mass: f64) -> usize {
+        let mut node = 1;
+
+        while node < self.leaves {
+            let left = self.nodes[2 * node];
+            if mass < left || self.nodes[2 * node + 1] == 0.0 {
+                node *= 2;
+            } else {
+                mass -= left;
+                node = 2 * node + 1;
+            }
+        }
+
+        node - self.leaves
+    }
+}
+
+/// A transition drawn from the buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample<S: State> {
+    /// position in the buffer, to update its priority afterwards
+    pub index: usize,
+    pub transition: Transition<S>,
+    /// importance sampling weight, 1 for uniform sampling
+    pub weight: f64,
+}
+
+/// A fixed capacity buffer of transitions to learn from.
+pub struct ReplayBuffer<S: State> {
+    transitions: Vec<Transition<S>>,
+    /// where the next transition goes, once the buffer is full
+    next: usize,
+    capacity: usize,
+    sampling: Sampling,
+    priorities: SumTree,
+    /// new transitions get the highest priority so far, to be sure they are learned from
+    max_priority: f64,
+}
+
+impl<S: State> ReplayBuffer<S> {
+    pub fn new(capacity: usize, sampling: Sampling) -> Self {
+        let capacity = capacity.max(1);
+
+        ReplayBuffer {
+            transitions: Vec::with_capacity(capacity),
+            next: 0,
+            capacity,
+            sampling,
+            priorities: SumTree::new(match sampling {
+                Sampling::Uniform => 1,
+                Sampling::Prioritized { .. } => capacity,
+            }),
+            max_priority: 1.0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// add a transition, replacing the oldest one if the buffer is full
+    pub fn push(&mut self, transition: Transition<S>) {
+        let index = if self.transitions.len() < self.capacity {
+            self.transitions.push(transition);
+            self.transitions.len() - 1
+        } else {
+            let index = self.next;
+            self.transitions[index] = transition;
+            self.next = (self.next + 1) % self.capacity;
+            index
+        };
+
+        if let Sampling::Prioritized { .. } = self.sampling {
+            self.priorities.set(index, self.max_priority);
+        }
+    }
+
+    /// Draw `amount` transitions (with replacement), following the sampling strategy.
+    pub fn sample<R: Rng + ?Sized>(&self, amount: usize, rng: &mut R) -> Vec<Sample<S>> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let Sampling::Prioritized { beta, .. } = self.sampling else {
+            return (0..amount)
+                .map(|_| {
+                    let index = rng.gen_range(0..self.len());
+                    Sample {
+                        index,
+                        transition: self.transitions[index].clone(),
+                        weight: 1.0,
+                    }
+                })
+                .collect();
+        };
+
+        // stratified: one transition from every equal part of the total priority
+        let total = self.priorities.total();
+        let segment = total / amount.max(1) as f64;
+        let n = self.len() as f64;
+
+        let mut samples: Vec<Sample<S>> = (0..amount)
+            .map(|k| {
+                let mass = segment * (k as f64 + rng.gen::<f64>());
+                let index = self.priorities.find(mass).min(self.len() - 1);
+                let probability = self.priorities.get(index) / total;
+
+                Sample {
+                    index,
+                    transition: self.transitions[index].clone(),
+                    weight: (n * probability).powf(-beta),
+                }
+            })
+            .collect();
+
+        // scale the weights down only, for stability
+        let max = samples.iter().map(|s| s.weight).fold(0.0, f64::max);
+        if max > 0.0 {
+            samples.iter_mut().for_each(|s| s.weight /= max);
+        }
+
+        samples
+    }
+
+    /// set the priorities of sampled transitions, given their new temporal difference errors
+    pub fn update_priorities(&mut self, indices: &[usize], td_errors: &[f64]) {
+        let Sampling::Prioritized { alpha, .. } = self.sampling else {
+            return;
+        };
+
+        for (&index, error) in indices.iter().zip(td_errors) {
+            let priority = (error.abs() + MIN_PRIORITY).powf(alpha);
+            self.max_priority = self.max_priority.max(priority);
+            self.priorities.set(index, priority);
+        }
+    }
+
+    /// change the importance sampling exponent, e.g. to anneal it towards 1 over the training
+    pub fn set_beta(&mut self, new_beta: f64) {
+        if let Sampling::Prioritized { beta, .. } = &mut self.sampling {
+            *beta = new_beta;
+        }
+    }
+}
+
+impl fmt::Display for Sampling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sampling::Uniform => write!(f, "uniform"),
+            Sampling::Prioritized { alpha, beta } => {
+                write!(f, "prioritized(alpha={alpha}, beta={beta})")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}(capacity={}, ratio={})",
+            self.sampling, self.capacity, self.ratio
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::testing::state;
+    use crate::gamestate::{Action, GameState};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn transition(reward: f64) -> Transition<GameState> {
+        Transition {
+            state: state(0),
+            action: Action::RaiseLow,
+            reward,
+            bootstrap: None,
+            discount: 1.0,
+        }
+    }
+
+    #[test]
+    fn sum_tree_finds_by_mass() {
+        let mut tree = SumTree::new(3);
+        tree.set(0, 1.0);
+        tree.set(1, 2.0);
+        tree.set(2, 3.0);
+
+        assert_eq!(tree.total(), 6.0);
+        assert_eq!(tree.find(0.5), 0);
+        assert_eq!(tree.find(2.5), 1);
+        assert_eq!(tree.find(5.9), 2);
+    }
+
+    #[test]
+    fn full_buffer_replaces_oldest() {
+        let mut buffer = ReplayBuffer::new(2, Sampling::Uniform);
+
+        for reward in 0..3 {
+            buffer.push(transition(reward as f64));
+        }
+
+        let rewards: Vec<f64> = buffer.transitions.iter().map(|t| t.reward).collect();
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(rewards, vec![2.0, 1.0]);
+    }
+
+    #[test]
+    fn prioritized_prefers_large_errors() {
+        let sampling = Sampling::Prioritized {
+            alpha: 1.0,
+            beta: 1.0,
+        };
+        let mut buffer = ReplayBuffer::new(4, sampling);
+        for reward in 0..4 {
+            buffer.push(transition(reward as f64));
+        }
+        buffer.update_priorities(&[0, 1, 2, 3], &[0.0, 0.0, 0.0, 10.0]);
+
+        let samples = buffer.sample(100, &mut ChaCha8Rng::seed_from_u64(0));
+        let picked = samples.iter().filter(|s| s.index == 3).count();
+
+        assert!(picked > 90);
+        // the most likely transition gets the smallest weight
+        let weight = samples.iter().find(|s| s.index == 3).unwrap().weight;
+        assert!(samples
+            .iter()
+            .all(|s| s.weight >= weight && s.weight <= 1.0));
+    }
+
+    #[test]
+    fn uniform_weights_are_one() {
+        let mut buffer = ReplayBuffer::new(10, Sampling::Uniform);
+        buffer.push(transition(1.0));
+
+        let samples = buffer.sample(5, &mut ChaCha8Rng::seed_from_u64(0));
+
+        assert_eq!(samples.len(), 5);
+        assert!(samples.iter().all(|s| s.index == 0 && s.weight == 1.0));
+    }
+}
@@ -0,0 +1,212 @@
+/*!
+Schedules describe how a hyperparameter (exploration rate, temperature, ...) evolves over the
+training iterations.
+*/
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How a value changes as training progresses, starting from some initial value.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum Schedule {
+    /// keep the initial value for the entire training
+    #[default]
+    Constant,
+    /// multiply the value by `decay` every iteration, but never go below `min`
+    Exponential { decay: f64, min: f64 },
+    /// go linearly from the initial value to `end` in `over` iterations, then stay at `end`
+    Linear { end: f64, over: u64 },
+}
+
+/// The kind of a schedule, without its parameters, see `Schedule::spanning`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScheduleKind {
+    #[default]
+    Constant,
+    Exponential,
+    Linear,
+}
+
+impl Schedule {
+    /// A schedule of `kind` that goes from `start` to `end` over `iterations`.
+    ///
+    /// Exponential schedules can only decay, so they need `0 < end <= start`.
+    pub fn spanning(
+        kind: ScheduleKind,
+        start: f64,
+        end: f64,
+        iterations: u64,
+    ) -> Result<Self, String> {
+        match kind {
+            ScheduleKind::Constant => Ok(Schedule::Constant),
+            ScheduleKind::Exponential => {
+                if !(0.0 < end && end <= start) {
+                    return Err(format!(
+                        "an exponential schedule can't go from {start} to {end}"
+                    ));
+                }
+                Ok(Schedule::Exponential {
+                    decay: (end / start).powf(1.0 / iterations.max(1) as f64),
+                    min: end,
+                })
+            }
+            ScheduleKind::Linear => Ok(Schedule::Linear {
+                end,
+                over: iterations,
+            }),
+        }
+    }
+
+    /// decay exponentially over `iterations`, until a twentieth of `start` is reached
+    pub fn annealing(start: f64, iterations: u64) -> Self {
+        Schedule::Exponential {
+            decay: 1.0 - 3.0 / iterations.max(1) as f64,
+            min: start / 20.0,
+        }
+    }
+
+    /// the value this schedule gives at `iteration`, when it started at `start`
+    pub fn value(&self, start: f64, iteration: u64) -> f64 {
+        match self {
+            Schedule::Constant => start,
+            Schedule::Exponential { decay, min } => {
+                // powi takes an i32, so clamp very long trainings
+                let exponent = iteration.min(i32::MAX as u64) as i32;
+                (start * decay.powi(exponent)).max(*min)
+            }
+            Schedule::Linear { end, over } => {
+                if *over == 0 || iteration >= *over {
+                    *end
+                } else {
+                    let progress = iteration as f64 / *over as f64;
+                    start + (end - start) * progress
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Constant => write!(f, "constant"),
+            Schedule::Exponential { decay, min } => {
+                write!(f, "exponential(decay={decay}, min={min})")
+            }
+            Schedule::Linear { end, over } => write!(f, "linear(end={end}, over={over})"),
+        }
+    }
+}
+
+impl fmt::Display for ScheduleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleKind::Constant => write!(f, "constant"),
+            ScheduleKind::Exponential => write!(f, "exponential"),
+            ScheduleKind::Linear => write!(f, "linear"),
+        }
+    }
+}
+
+/// How the learning rate of a single state-action pair decays with the amount of times it has
+/// been updated. The result scales the (scheduled) global learning rate.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum RateDecay {
+    /// every update uses the global learning rate
+    #[default]
+    Constant,
+    /// the n-th update of a pair uses `rate / n`, with a rate of one this is a running average
+    InverseVisits,
+    /// the n-th update of a pair uses `rate / n^exponent`, the exponent usually is in (0.5, 1]
+    Polynomial(f64),
+}
+
+impl RateDecay {
+    /// decay with `1 / n^exponent`, an exponent of 0 is constant and 1 is a running average
+    pub fn with_exponent(exponent: f64) -> Self {
+        if exponent == 0.0 {
+            RateDecay::Constant
+        } else if exponent == 1.0 {
+            RateDecay::InverseVisits
+        } else {
+            RateDecay::Polynomial(exponent)
+        }
+    }
+
+    /// learning rate for the `visits`-th update of a pair, given the global `rate`
+    pub fn rate(&self, rate: f64, visits: u64) -> f64 {
+        let visits = visits.max(1) as f64;
+
+        match self {
+            RateDecay::Constant => rate,
+            RateDecay::InverseVisits => rate / visits,
+            RateDecay::Polynomial(exponent) => rate / visits.powf(*exponent),
+        }
+    }
+}
+
+impl fmt::Display for RateDecay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateDecay::Constant => write!(f, "constant"),
+            RateDecay::InverseVisits => write!(f, "1/n"),
+            RateDecay::Polynomial(exponent) => write!(f, "1/n^{exponent}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_visits_is_running_average() {
+        let decay = RateDecay::InverseVisits;
+
+        assert_eq!(decay.rate(1.0, 1), 1.0);
+        assert_eq!(decay.rate(1.0, 4), 0.25);
+    }
+
+    #[test]
+    fn polynomial_decay() {
+        let decay = RateDecay::Polynomial(0.5);
+
+        assert_eq!(decay.rate(0.5, 4), 0.25);
+    }
+
+    #[test]
+    fn exponential_respects_minimum() {
+        let schedule = Schedule::Exponential {
+            decay: 0.5,
+            min: 0.1,
+        };
+
+        assert_eq!(schedule.value(1.0, 1), 0.5);
+        assert_eq!(schedule.value(1.0, 100), 0.1);
+    }
+
+    #[test]
+    fn spanning_ends_at_end() {
+        let iterations = 1000;
+
+        for kind in [ScheduleKind::Exponential, ScheduleKind::Linear] {
+            let schedule = Schedule::spanning(kind, 0.4, 0.02, iterations).unwrap();
+
+            assert_eq!(schedule.value(0.4, 0), 0.4);
+            assert!(
+                (schedule.value(0.4, iterations) - 0.02).abs() < 1e-9,
+                "{kind}"
+            );
+        }
+        assert!(Schedule::spanning(ScheduleKind::Exponential, 0.2, 0.8, iterations).is_err());
+    }
+
+    #[test]
+    fn linear_stays_at_end() {
+        let schedule = Schedule::Linear { end: 0.0, over: 10 };
+
+        assert_eq!(schedule.value(1.0, 5), 0.5);
+        assert_eq!(schedule.value(1.0, 20), 0.0);
+    }
+}
@@ -0,0 +1,177 @@
+/*!
+A Q table that can be read and updated by many training threads at the same time.
+
+The states are spread over shards by their hash, every shard has its own lock.
+A thread only locks the shard of the state it looks at, so threads rarely wait on each other.
+*/
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use super::{State, Visits, Q};
+
+/// The part of the tables that holds the states with the same hash.
+pub struct Shard<S: State> {
+    /// the second table is only used by double Q-learning
+    pub tables: [Q<S>; 2],
+    pub visits: Visits<S>,
+}
+
+impl<S: State> Default for Shard<S> {
+    fn default() -> Self {
+        Shard {
+            tables: [Q::new(), Q::new()],
+            visits: Visits::new(),
+        }
+    }
+}
+
+/// Both Q tables and the visit counts, sharded by state.
+pub struct SharedQ<S: State> {
+    shards: Vec<RwLock<Shard<S>>>,
+}
+
+impl<S: State> SharedQ<S> {
+    /// spread the given tables over `shards` shards
+    pub fn new(tables: [Q<S>; 2], visits: Visits<S>, shards: usize) -> Self {
+        let mut split: Vec<Shard<S>> = (0..shards.max(1)).map(|_| Shard::default()).collect();
+        let n = split.len();
+
+        let [q_a, q_b] = tables;
+        for (i, table) in [q_a, q_b].into_iter().enumerate() {
+            for (state, actions) in table {
+                split[shard_index(&state, n)].tables[i].insert(state, actions);
+            }
+        }
+        for (state, counts) in visits {
+            split[shard_index(&state, n)].visits.insert(state, counts);
+        }
+
+        SharedQ {
+            shards: split.into_iter().map(RwLock::new).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.is_empty()
+    }
+
+    /// index of the shard that holds `state`
+    pub fn shard_of(&self, state: &S) -> usize {
+        shard_index(state, self.shards.len())
+    }
+
+    /// lock the shard of `state` for reading
+    pub fn read(&self, state: &S) -> RwLockReadGuard<'_, Shard<S>> {
+        self.shards[self.shard_of(state)].read().unwrap()
+    }
+
+    /// lock a shard for writing
+    pub fn write(&self, shard: usize) -> RwLockWriteGuard<'_, Shard<S>> {
+        self.shards[shard].write().unwrap()
+    }
+
+    /// Merge the shards back into whole tables and visit counts.
+    ///
+    /// *note: the shards are locked one at a time, updates can happen in between*
+    pub fn snapshot(&self) -> ([Q<S>; 2], Visits<S>) {
+        let mut tables = [Q::new(), Q::new()];
+        let mut visits = Visits::new();
+
+        for shard in &self.shards {
+            let shard = shard.read().unwrap();
+            for (table, part) in tables.iter_mut().zip(&shard.tables) {
+                table.extend(part.iter().map(|(s, a)| (*s, a.clone())));
+            }
+            visits.extend(shard.visits.iter().map(|(s, v)| (*s, v.clone())));
+        }
+
+        (tables, visits)
+    }
+
+    /// one of both tables as a whole, see `snapshot`
+    pub fn table(&self, index: usize) -> Q<S> {
+        let mut table = Q::new();
+
+        for shard in &self.shards {
+            let shard = shard.read().unwrap();
+            table.extend(shard.tables[index].iter().map(|(s, a)| (*s, a.clone())));
+        }
+
+        table
+    }
+
+    /// take the tables and visit counts out again
+    pub fn into_parts(self) -> ([Q<S>; 2], Visits<S>) {
+        let mut tables = [Q::new(), Q::new()];
+        let mut visits = Visits::new();
+
+        for shard in self.shards {
+            let shard = shard.into_inner().unwrap();
+            for (table, part) in tables.iter_mut().zip(shard.tables) {
+                table.extend(part);
+            }
+            visits.extend(shard.visits);
+        }
+
+        (tables, visits)
+    }
+}
+
+fn shard_index<S: Hash>(state: &S, shards: usize) -> usize {
+    // the default hasher with its default keys, so a state always ends up in the same shard
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    (hasher.finish() % shards as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::testing::state;
+    use crate::gamestate::{Action, GameState};
+
+    #[test]
+    fn shards_hold_their_states() {
+        let mut q = Q::new();
+        for nb_cards in 0..13 {
+            q.insert(
+                state(nb_cards),
+                [(Action::RaiseLow, nb_cards as f64)].into_iter().collect(),
+            );
+        }
+
+        let shared = SharedQ::new([q.clone(), Q::new()], Visits::new(), 4);
+
+        for nb_cards in 0..13 {
+            let shard = shared.read(&state(nb_cards));
+            assert_eq!(
+                shard.tables[0][&state(nb_cards)][&Action::RaiseLow],
+                nb_cards as f64
+            );
+        }
+
+        let ([a, b], _) = shared.into_parts();
+        assert_eq!(a, q);
+        assert!(b.is_empty());
+    }
+
+    #[test]
+    fn snapshot_sees_updates() {
+        let shared: SharedQ<GameState> = SharedQ::new([Q::new(), Q::new()], Visits::new(), 8);
+
+        let shard = shared.shard_of(&state(3));
+        shared.write(shard).tables[1]
+            .entry(state(3))
+            .or_default()
+            .insert(Action::RaiseHigh, 0.5);
+
+        assert_eq!(shared.table(1)[&state(3)][&Action::RaiseHigh], 0.5);
+        assert!(shared.snapshot().0[0].is_empty());
+    }
+}
@@ -0,0 +1,791 @@
+/*!
+Hyperparameter search for a `QLearner`.
+
+A `TuneConfig`, usually read from a toml file, describes the values to try for every
+hyperparameter and how to search through them:
+- `grid`: every combination of the values,
+- `random`: a number of random combinations,
+- `halving`: successive halving, random combinations are trained shortly and only the best
+  part of them is trained longer, until one is left.
+
+Every trial is trained with multiple seeds, and trials run in parallel.
+
+Example config:
+```toml
+strategy = "random"
+samples = 20
+seeds = 3
+iterations = 500000
+
+[space]
+rate = { min = 0.01, max = 0.2, log = true }
+discount = [0.1, 0.2, 0.4]
+discount_schedule = ["constant", "linear"]
+exploration = 0.3
+algorithm = ["q-learning", "sarsa"]
+```
+*/
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use super::{Algorithm, Policy, QLearner, RateDecay, Schedule, ScheduleKind, State};
+
+/// How the search space is explored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    /// try every combination of the values
+    #[default]
+    Grid,
+    /// try `samples` random combinations
+    Random,
+    /// successive halving of `samples` random combinations
+    Halving,
+}
+
+/// The exploration policy the trials train with, `exploration` is its epsilon or temperature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Exploration {
+    #[default]
+    Random,
+    Epsilon,
+    Softmax,
+}
+
+/// The state representation a trial learns, only playing cards can be learned for now.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum StateType {
+    /// playing cards, see `gamestate::GameState`
+    #[default]
+    Game,
+}
+
+/// The values one hyperparameter can take.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Values {
+    /// always this value
+    One(f64),
+    /// one of these values
+    List(Vec<f64>),
+    /// in between `min` and `max`, a grid uses `steps` values
+    Range {
+        min: f64,
+        max: f64,
+        #[serde(default = "default_steps")]
+        steps: usize,
+        /// spread the values logarithmically, e.g. for learning rates
+        #[serde(default)]
+        log: bool,
+    },
+}
+
+fn default_steps() -> usize {
+    5
+}
+
+impl Values {
+    /// the values a grid search tries
+    pub fn grid(&self) -> Vec<f64> {
+        match self {
+            Values::One(value) => vec![*value],
+            Values::List(values) => values.clone(),
+            Values::Range {
+                min,
+                max,
+                steps,
+                log,
+            } => {
+                if *steps < 2 {
+                    return vec![*min];
+                }
+                (0..*steps)
+                    .map(|i| {
+                        let t = i as f64 / (*steps - 1) as f64;
+                        match log {
+                            true => (min.ln() + (max.ln() - min.ln()) * t).exp(),
+                            false => min + (max - min) * t,
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// a random value, for random search
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        match self {
+            Values::One(value) => *value,
+            Values::List(values) => *values.choose(rng).expect("List should not be empty"),
+            Values::Range { min, max, log, .. } => {
+                let t: f64 = rng.gen();
+                match log {
+                    true => (min.ln() + (max.ln() - min.ln()) * t).exp(),
+                    false => min + (max - min) * t,
+                }
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        matches!(self, Values::List(values) if values.is_empty())
+    }
+
+    /// the lowest value that can be tried
+    fn lowest(&self) -> f64 {
+        match self {
+            Values::One(value) => *value,
+            Values::List(values) => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Values::Range { min, .. } => *min,
+        }
+    }
+}
+
+/// The values to try for every hyperparameter.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Space {
+    pub rate: Values,
+    /// how the learning rate changes, to a twentieth of its start
+    pub rate_schedule: Vec<ScheduleKind>,
+    /// the learning rate of a state-action pair is `rate / visits^rate_decay`
+    pub rate_decay: Values,
+    pub discount: Values,
+    /// how the discount changes, to a twentieth of its start
+    pub discount_schedule: Vec<ScheduleKind>,
+    pub initial_value: Values,
+    pub queue_size: Values,
+    /// epsilon or temperature of the exploration policy
+    pub exploration: Values,
+    pub algorithm: Vec<Algorithm>,
+    pub state: Vec<StateType>,
+}
+
+impl Default for Space {
+    fn default() -> Self {
+        Space {
+            rate: Values::One(0.05),
+            rate_schedule: vec![ScheduleKind::Constant],
+            rate_decay: Values::One(0.0),
+            discount: Values::One(0.2),
+            discount_schedule: vec![ScheduleKind::Constant],
+            initial_value: Values::One(0.8),
+            queue_size: Values::One(500.0),
+            exploration: Values::One(0.3),
+            algorithm: vec![Algorithm::QLearning],
+            state: vec![StateType::Game],
+        }
+    }
+}
+
+/// Describes a hyperparameter search.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TuneConfig {
+    pub strategy: Strategy,
+    /// iterations every trial is trained, the most a trial gets when halving
+    pub iterations: u64,
+    /// amount of seeds every trial is trained with
+    pub seeds: usize,
+    /// amount of combinations for random search and successive halving
+    pub samples: usize,
+    /// successive halving keeps the best `1 / eta` of the trials in every round
+    pub eta: usize,
+    /// amount of trainings that run at the same time
+    pub parallel: usize,
+    /// amount of deals every trained model is evaluated on
+    pub eval_deals: usize,
+    pub self_play: bool,
+    pub policy: Exploration,
+    /// seed of the search itself, random by default
+    pub seed: Option<u64>,
+    /// csv or json (by extension) file to write the results to
+    pub output: String,
+    pub space: Space,
+}
+
+impl Default for TuneConfig {
+    /// the original tuning: a 9x9 grid of rate and discount for every algorithm
+    fn default() -> Self {
+        TuneConfig {
+            strategy: Strategy::Grid,
+            iterations: 1000000,
+            seeds: 1,
+            samples: 20,
+            eta: 3,
+            parallel: 1,
+            eval_deals: 3077,
+            self_play: true,
+            policy: Exploration::Random,
+            seed: None,
+            output: String::from("out.csv"),
+            space: Space {
+                rate: Values::Range {
+                    min: 0.02,
+                    max: 0.18,
+                    steps: 9,
+                    log: false,
+                },
+                discount: Values::Range {
+                    min: 0.1,
+                    max: 0.9,
+                    steps: 9,
+                    log: false,
+                },
+                algorithm: Algorithm::all().to_vec(),
+                ..Space::default()
+            },
+        }
+    }
+}
+
+/// One combination of hyperparameters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Trial {
+    pub state: StateType,
+    pub algorithm: Algorithm,
+    pub rate: f64,
+    pub rate_schedule: ScheduleKind,
+    pub rate_decay: f64,
+    pub discount: f64,
+    pub discount_schedule: ScheduleKind,
+    pub initial_value: f64,
+    pub queue_size: usize,
+    pub exploration: f64,
+}
+
+/// One training of a trial.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Run {
+    pub iterations: u64,
+    pub seed: u64,
+    /// amount of threads the training may use
+    pub threads: usize,
+}
+
+/// The scores of a trial over all its seeds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrialResult {
+    pub trial: Trial,
+    pub iterations: u64,
+    pub scores: Vec<f64>,
+    /// mean of the scores
+    pub score: f64,
+    /// sample standard deviation of the scores
+    pub score_std: f64,
+}
+
+/// A run of the search that is done, see `TuneConfig::search`.
+#[derive(Clone, Copy, Debug)]
+pub struct Progress<'a> {
+    /// amount of runs done in this round, including this one
+    pub done: usize,
+    /// amount of runs in this round
+    pub runs: usize,
+    pub trial: &'a Trial,
+    pub iterations: u64,
+    pub score: f64,
+}
+
+impl TrialResult {
+    pub fn new(trial: Trial, iterations: u64, scores: Vec<f64>) -> Self {
+        let n = scores.len() as f64;
+        let score = scores.iter().sum::<f64>() / n;
+        let score_std = match scores.len() {
+            0 | 1 => 0.0,
+            _ => (scores.iter().map(|s| (s - score).powi(2)).sum::<f64>() / (n - 1.0)).sqrt(),
+        };
+
+        TrialResult {
+            trial,
+            iterations,
+            scores,
+            score,
+            score_std,
+        }
+    }
+}
+
+impl TuneConfig {
+    /// Read a config from a toml file, missing settings get their default value.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+
+        let config: TuneConfig =
+            toml::from_str(&contents).map_err(|e| format!("{}: {e}", path.display()))?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// check the settings that can't be checked by their type
+    pub fn validate(&self) -> Result<(), String> {
+        let space = &self.space;
+        let values = [
+            ("rate", &space.rate),
+            ("rate_decay", &space.rate_decay),
+            ("discount", &space.discount),
+            ("initial_value", &space.initial_value),
+            ("queue_size", &space.queue_size),
+            ("exploration", &space.exploration),
+        ];
+        for (name, values) in values {
+            if values.is_empty() {
+                return Err(format!("{name} has no values to try"));
+            }
+            if let Values::Range { min, log: true, .. } = values {
+                if *min <= 0.0 {
+                    return Err(format!("{name} can only be spread logarithmically above 0"));
+                }
+            }
+        }
+
+        if space.algorithm.is_empty()
+            || space.state.is_empty()
+            || space.rate_schedule.is_empty()
+            || space.discount_schedule.is_empty()
+        {
+            return Err(String::from(
+                "algorithm, state and the schedules need at least one value",
+            ));
+        }
+
+        // exponential schedules can only decay from a positive value
+        let schedules = [
+            ("rate", &space.rate_schedule, &space.rate),
+            ("discount", &space.discount_schedule, &space.discount),
+        ];
+        for (name, schedules, values) in schedules {
+            if schedules.contains(&ScheduleKind::Exponential) && values.lowest() <= 0.0 {
+                return Err(format!(
+                    "an exponential {name} schedule needs a {name} above 0"
+                ));
+            }
+        }
+        if self.seeds == 0 || self.parallel == 0 {
+            return Err(String::from("seeds and parallel should be at least 1"));
+        }
+        if self.strategy == Strategy::Halving && self.eta < 2 {
+            return Err(String::from("eta should be at least 2"));
+        }
+
+        Ok(())
+    }
+
+    /// the combinations of hyperparameters to try, following the strategy
+    pub fn trials<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<Trial> {
+        let space = &self.space;
+
+        if self.strategy != Strategy::Grid {
+            return (0..self.samples)
+                .map(|_| Trial {
+                    state: *space.state.choose(rng).unwrap(),
+                    algorithm: *space.algorithm.choose(rng).unwrap(),
+                    rate: space.rate.sample(rng),
+                    rate_schedule: *space.rate_schedule.choose(rng).unwrap(),
+                    rate_decay: space.rate_decay.sample(rng),
+                    discount: space.discount.sample(rng),
+                    discount_schedule: *space.discount_schedule.choose(rng).unwrap(),
+                    initial_value: space.initial_value.sample(rng),
+                    queue_size: space.queue_size.sample(rng).round().max(1.0) as usize,
+                    exploration: space.exploration.sample(rng),
+                })
+                .collect();
+        }
+
+        // every combination, built up one hyperparameter at a time
+        let mut trials = vec![Trial::default()];
+        trials = expand(trials, &space.state, |t, v| t.state = v);
+        trials = expand(trials, &space.algorithm, |t, v| t.algorithm = v);
+        trials = expand(trials, &space.discount.grid(), |t, v| t.discount = v);
+        trials = expand(trials, &space.discount_schedule, |t, v| {
+            t.discount_schedule = v
+        });
+        trials = expand(trials, &space.rate.grid(), |t, v| t.rate = v);
+        trials = expand(trials, &space.rate_schedule, |t, v| t.rate_schedule = v);
+        trials = expand(trials, &space.rate_decay.grid(), |t, v| t.rate_decay = v);
+        trials = expand(trials, &space.initial_value.grid(), |t, v| {
+            t.initial_value = v
+        });
+        trials = expand(trials, &space.queue_size.grid(), |t, v| {
+            t.queue_size = v.round().max(1.0) as usize
+        });
+        expand(trials, &space.exploration.grid(), |t, v| t.exploration = v)
+    }
+
+    /// a learner that is set up to do `run` of `trial`
+    pub fn learner<S: State + 'static>(&self, trial: &Trial, run: &Run) -> QLearner<S> {
+        let mut learner = QLearner::new_with_iter(run.iterations);
+
+        let schedule = |kind, start: f64| {
+            Schedule::spanning(kind, start, start / 20.0, run.iterations)
+                .expect("exponential schedules are validated to start above 0")
+        };
+
+        learner.rate = trial.rate;
+        learner.rate_schedule = schedule(trial.rate_schedule, trial.rate);
+        learner.rate_decay = RateDecay::with_exponent(trial.rate_decay);
+        learner.discount = trial.discount;
+        learner.discount_schedule = schedule(trial.discount_schedule, trial.discount);
+        learner.initial_value = trial.initial_value;
+        learner.queue_size = trial.queue_size;
+        learner.algorithm = trial.algorithm;
+
+        let schedule = Schedule::annealing(trial.exploration, run.iterations);
+        learner.policy = match self.policy {
+            Exploration::Random => Policy::Random,
+            Exploration::Epsilon => Policy::EpsilonGreedy {
+                epsilon: trial.exploration,
+                schedule,
+            },
+            Exploration::Softmax => Policy::Softmax {
+                temperature: trial.exploration,
+                schedule,
+            },
+        };
+
+        if self.self_play {
+            learner.enable_self_play();
+        }
+        learner.set_seed(run.seed);
+        learner.set_threads(run.threads);
+        learner.disable_verbose();
+
+        learner
+    }
+
+    /// Run the search, `evaluate` trains and scores one run of a trial,
+    /// `progress` is told about every run that is done.
+    ///
+    /// Returns the results of every trial, for successive halving this includes every round.
+    pub fn search<F, P>(&self, evaluate: F, progress: P) -> Vec<TrialResult>
+    where
+        F: Fn(&Trial, &Run) -> f64 + Sync,
+        P: Fn(Progress) + Sync,
+    {
+        let mut rng = match self.seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
+
+        // every trial uses the same seeds, to compare them fairly
+        let seeds: Vec<u64> = (0..self.seeds).map(|_| rng.gen()).collect();
+
+        let trials = self.trials(&mut rng);
+
+        if self.strategy != Strategy::Halving {
+            return self.run_all(&trials, self.iterations, &seeds, &evaluate, &progress);
+        }
+
+        // the amount of rounds needed to get to one trial
+        let mut rounds = 0;
+        let mut left = trials.len();
+        while left > 1 {
+            left = left.div_ceil(self.eta);
+            rounds += 1;
+        }
+
+        let mut results = Vec::new();
+        let mut trials = trials;
+
+        for round in 0..=rounds {
+            let shrink = (self.eta as u64).pow(rounds - round);
+            let iterations = (self.iterations / shrink).max(1);
+
+            let mut round_results = self.run_all(&trials, iterations, &seeds, &evaluate, &progress);
+            results.extend(round_results.iter().cloned());
+
+            round_results.sort_by(|a, b| b.score.total_cmp(&a.score));
+            let keep = trials.len().div_ceil(self.eta);
+            trials = round_results
+                .into_iter()
+                .take(keep)
+                .map(|result| result.trial)
+                .collect();
+        }
+
+        results
+    }
+
+    /// train every trial with every seed, `parallel` trainings at a time
+    fn run_all<F, P>(
+        &self,
+        trials: &[Trial],
+        iterations: u64,
+        seeds: &[u64],
+        evaluate: &F,
+        progress: &P,
+    ) -> Vec<TrialResult>
+    where
+        F: Fn(&Trial, &Run) -> f64 + Sync,
+        P: Fn(Progress) + Sync,
+    {
+        let jobs: Vec<(usize, u64)> = (0..trials.len())
+            .flat_map(|trial| seeds.iter().map(move |seed| (trial, *seed)))
+            .collect();
+
+        let threads = (num_cpus::get() / self.parallel).max(2);
+        let next = AtomicUsize::new(0);
+        let scores = Mutex::new(vec![Vec::new(); trials.len()]);
+
+        thread::scope(|scope| {
+            for _ in 0..self.parallel.min(jobs.len()) {
+                scope.spawn(|| loop {
+                    let job = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&(trial, seed)) = jobs.get(job) else {
+                        break;
+                    };
+
+                    let run = Run {
+                        iterations,
+                        seed,
+                        threads,
+                    };
+                    let score = evaluate(&trials[trial], &run);
+
+                    let mut scores = scores.lock().unwrap();
+                    scores[trial].push(score);
+                    let done: usize = scores.iter().map(Vec::len).sum();
+                    progress(Progress {
+                        done,
+                        runs: jobs.len(),
+                        trial: &trials[trial],
+                        iterations,
+                        score,
+                    });
+                });
+            }
+        });
+
+        trials
+            .iter()
+            .zip(scores.into_inner().unwrap())
+            .map(|(trial, scores)| TrialResult::new(*trial, iterations, scores))
+            .collect()
+    }
+}
+
+/// every one of `trials` with every one of `values`, which `set` puts in the trial
+fn expand<T: Copy>(trials: Vec<Trial>, values: &[T], set: fn(&mut Trial, T)) -> Vec<Trial> {
+    trials
+        .into_iter()
+        .flat_map(|trial| {
+            values.iter().map(move |value| {
+                let mut trial = trial;
+                set(&mut trial, *value);
+                trial
+            })
+        })
+        .collect()
+}
+
+/// Write the results to a json file, or a csv file with one line per trial.
+pub fn write_results(results: &[TrialResult], path: impl AsRef<Path>) -> Result<(), String> {
+    let path = path.as_ref();
+
+    if path.extension().is_some_and(|e| e == "json") {
+        let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        return serde_json::to_writer_pretty(file, results).map_err(|e| e.to_string());
+    }
+
+    let mut wtr = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+    wtr.write_record([
+        "state",
+        "algorithm",
+        "discount",
+        "discount_schedule",
+        "learning_rate",
+        "rate_schedule",
+        "rate_decay",
+        "initial_value",
+        "queue_size",
+        "exploration",
+        "iterations",
+        "seeds",
+        "score",
+        "score_std",
+    ])
+    .map_err(|e| e.to_string())?;
+
+    for result in results {
+        let trial = &result.trial;
+        wtr.write_record([
+            trial.state.to_string(),
+            trial.algorithm.to_string(),
+            trial.discount.to_string(),
+            trial.discount_schedule.to_string(),
+            trial.rate.to_string(),
+            trial.rate_schedule.to_string(),
+            trial.rate_decay.to_string(),
+            trial.initial_value.to_string(),
+            trial.queue_size.to_string(),
+            trial.exploration.to_string(),
+            result.iterations.to_string(),
+            result.scores.len().to_string(),
+            result.score.to_string(),
+            result.score_std.to_string(),
+        ])
+        .map_err(|e| e.to_string())?;
+    }
+
+    wtr.flush().map_err(|e| e.to_string())
+}
+
+impl fmt::Display for StateType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateType::Game => write!(f, "game"),
+        }
+    }
+}
+
+impl fmt::Display for Trial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} rate={:.4} ({}, decay={:.2}) discount={:.4} ({}) initial_value={:.4} \
+             queue_size={} exploration={:.4}",
+            self.state,
+            self.algorithm,
+            self.rate,
+            self.rate_schedule,
+            self.rate_decay,
+            self.discount,
+            self.discount_schedule,
+            self.initial_value,
+            self.queue_size,
+            self.exploration
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_grid_includes_bounds() {
+        let values = Values::Range {
+            min: 0.01,
+            max: 1.0,
+            steps: 3,
+            log: true,
+        };
+
+        let grid = values.grid();
+
+        assert_eq!(grid.len(), 3);
+        assert!((grid[0] - 0.01).abs() < 1e-9);
+        assert!((grid[1] - 0.1).abs() < 1e-9);
+        assert!((grid[2] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn grid_tries_every_combination() {
+        let config: TuneConfig = toml::from_str(
+            r#"
+            [space]
+            rate = [0.01, 0.02]
+            discount = { min = 0.1, max = 0.9, steps = 3 }
+            algorithm = ["q-learning", "sarsa"]
+            "#,
+        )
+        .unwrap();
+
+        let trials = config.trials(&mut ChaCha8Rng::seed_from_u64(0));
+
+        assert_eq!(trials.len(), 12);
+        assert_eq!(config.space.initial_value, Values::One(0.8));
+    }
+
+    #[test]
+    fn invalid_spaces_are_rejected() {
+        let parse = |space: &str| {
+            toml::from_str::<TuneConfig>(&format!("[space]\n{space}"))
+                .map_err(|e| e.to_string())
+                .and_then(|config| config.validate())
+        };
+
+        assert!(parse("rate = { min = 0.01, max = 0.1, log = true }").is_ok());
+        assert!(parse("rate = { min = 0.0, max = 0.1, log = true }").is_err());
+        assert!(parse("state = [\"bid\"]").is_err());
+    }
+
+    #[test]
+    fn exponential_schedules_need_positive_values() {
+        let mut config = TuneConfig::default();
+        config.space.discount_schedule = vec![ScheduleKind::Linear, ScheduleKind::Exponential];
+        assert!(config.validate().is_ok());
+
+        config.space.discount = Values::List(vec![0.0, 0.5]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn random_samples_within_range() {
+        let config = TuneConfig {
+            strategy: Strategy::Random,
+            samples: 50,
+            ..TuneConfig::default()
+        };
+
+        let trials = config.trials(&mut ChaCha8Rng::seed_from_u64(0));
+
+        assert_eq!(trials.len(), 50);
+        assert!(trials.iter().all(|t| (0.02..=0.18).contains(&t.rate)));
+    }
+
+    #[test]
+    fn halving_trains_the_best_longest() {
+        let config = TuneConfig {
+            strategy: Strategy::Halving,
+            samples: 9,
+            seeds: 2,
+            iterations: 900,
+            seed: Some(1),
+            ..TuneConfig::default()
+        };
+
+        let results = config.search(|trial, _| trial.rate, |_| {});
+
+        // 9 trials, then 3, then 1
+        assert_eq!(results.len(), 13);
+
+        let last = results.last().unwrap();
+        assert_eq!(last.iterations, 900);
+        assert_eq!(last.scores.len(), 2);
+
+        let best = results.iter().map(|r| r.trial.rate).fold(0.0, f64::max);
+        assert_eq!(last.trial.rate, best);
+    }
+
+    #[test]
+    fn result_variance() {
+        let trial = TuneConfig::default().trials(&mut ChaCha8Rng::seed_from_u64(0))[0];
+
+        let result = TrialResult::new(trial, 1, vec![1.0, 3.0]);
+
+        assert_eq!(result.score, 2.0);
+        assert!((result.score_std - 2.0f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn example_config_is_valid() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tune.toml");
+
+        let config = TuneConfig::from_file(path).unwrap();
+
+        assert_eq!(config.strategy, Strategy::Halving);
+    }
+}
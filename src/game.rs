@@ -6,10 +6,15 @@ This module defines a game of colour whist, which consists of tricks and the cur
 You can play a whister game like this:
 ```no_run
 use whister::game::Game;
+use whister::tui::Tui;
 
 let mut game = Game::new();
 game.add_human_players(1).unwrap();
 
+// the game is played full-screen, until `tui` is dropped
+// (any `whister::ui::Ui` will do, like `Plain` lines when stdout is not a terminal)
+let mut tui = Tui::start().unwrap();
+
 // example: three deals
 let mut count = 3;
 loop {
@@ -19,7 +24,7 @@ loop {
     count -= 1;
 
     // None supplied for just a simple rule-based opponent
-    game.play_deal(&None);
+    game.play_deal(&mut tui, &None);
 
     // start a new deal
     game.new_deal();
@@ -28,19 +33,23 @@ loop {
 
 # Playing against a trained AI model
 For this, you need to use a `.bin` file, a serialized fortify::Q object.
-These are supplied on the github in the `data/` directory,
-or can be trained using the `fortify` module.
+The ones in the `data/models` directory are built in as `easy`, `medium` and `hard`,
+others can be trained using the `fortify` module.
 
 for example:
 ```no_run
 use whister::game::Game;
 use whister::fortify;
+use whister::tui::Tui;
 
 let mut game = Game::new();
 game.add_human_players(1).unwrap();
+let mut tui = Tui::start().unwrap();
 
-// the model is deserialized here (example file path)
-let q = fortify::data::bin_to_q("~/.local/share/whister/easy.bin", false);
+// a model file, a model saved in the data directory, or a built-in model
+let q = fortify::data::bin_to_q("~/models/mine.bin")
+    .or_else(|_| fortify::data::bin_to_q("easy"))
+    .ok();
 
 // example: three deals
 let mut count = 3;
@@ -51,7 +60,7 @@ loop {
     count -= 1;
 
     // None supplied for just a simple rule-based opponent
-    game.play_deal(&q);
+    game.play_deal(&mut tui, &q);
 
     // start a new deal
     game.new_deal();
@@ -61,26 +70,35 @@ loop {
 
 use crate::{
     card::Card,
+    cardset::CardSet,
     deck::{CardID, Deck},
-    fortify::{self, GameSpace, Q},
+    fortify::{self, data::DataError, GameSpace, MultiAgentSpace, Q},
     gamestate::{Action, BidState, GameState},
     player::Player,
-    show,
+    render, review,
+    scoreboard::{DealRecord, Scoreboard},
+    search, show,
     suit::Suit,
+    ui::Ui,
 };
 use itertools::Itertools;
-use std::{
-    cmp::Ordering,
-    io::{stdin, stdout, Write},
-    process::exit,
-};
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use termion::event::Key;
-use termion::input::TermRead;
-use termion::raw::IntoRawMode;
 
 type PlayerID = usize;
 
-#[derive(Hash, Eq, PartialEq)]
+/// what the human did when asked for a card
+enum Choice {
+    Play,
+    Undo,
+    Quit,
+}
+
+/// A game can be saved mid-deal and resumed, see `to_json`.
+#[derive(Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Game {
     /// tricks keeps track of the played tricks
     /// one trick is a deck of 4 cards
@@ -91,15 +109,30 @@ pub struct Game {
     pub players: [Deck; 4],
     turn: PlayerID,
     trump: Suit,
-    scores: [u32; 4],
     human_players: usize,
     round_scores: [u32; 4],
-    gone_cards: [[bool; 13]; 4],
+    /// the cards that have been played in earlier tricks of this deal
+    gone_cards: CardSet,
     last_winner: usize,
     last_last_winner: usize,
     dealer: usize,
     bidding: bool,
     nb_cant_follow: [u8; 4],
+    /// for every player, the suits they didn't follow in earlier tricks of this deal
+    #[serde(default)]
+    voids: [[bool; 4]; 4],
+    /// the team of every player, a trick is won by the whole team
+    teams: [usize; 4],
+    /// in practice mode, the human player can take back cards
+    #[serde(default)]
+    practice: bool,
+    /// the earlier deals of this game
+    #[serde(default)]
+    scoreboard: Scoreboard,
+    /// the game before every card of a human player this deal,
+    /// to go back to in practice mode and to review the deal with
+    #[serde(skip)]
+    history: Vec<Game>,
 }
 
 impl Default for Game {
@@ -122,7 +155,6 @@ impl Game {
         let player_four = Player::new_take_cards(&mut deck, 13);
 
         let players = [player_one, player_two, player_three, player_four];
-        let scores = [0, 0, 0, 0];
 
         Game {
             tricks,
@@ -130,43 +162,187 @@ impl Game {
             players,
             turn: 0,
             trump: Suit::Hearts,
-            scores,
             human_players: 0,
             round_scores: [0; 4],
-            gone_cards: [[false; 13]; 4],
+            gone_cards: CardSet::EMPTY,
             last_winner: 0,
             last_last_winner: 0,
             dealer: 0,
             bidding: true,
             nb_cant_follow: [0; 4],
+            voids: [[false; 4]; 4],
+            teams: [0, 1, 2, 3],
+            practice: false,
+            scoreboard: Scoreboard::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// The game as JSON, with cards in their notation, e.g. `"players": [["2S", "9S", ..], ..]`.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// a game from `to_json`, which is checked with `validate`
+    pub fn from_json(json: &str) -> Result<Game, DataError> {
+        let game: Game = serde_json::from_str(json)?;
+        game.validate().map_err(DataError::Invalid)?;
+        Ok(game)
+    }
+
+    /// Does the game hold together? A game read from a file can have players out of range,
+    /// or cards that are missing or dealt twice, which the engine doesn't expect.
+    pub fn validate(&self) -> Result<(), String> {
+        let seats = [
+            self.turn,
+            self.dealer,
+            self.last_winner,
+            self.last_last_winner,
+        ];
+        if seats.iter().chain(&self.teams).any(|&player| player >= 4) {
+            return Err("there are only players 0 to 3".to_string());
+        }
+        if self.human_players > 4 {
+            return Err("there are more than 4 human players".to_string());
+        }
+        if self.tricks.len() > 13 || self.tricks.iter().any(|trick| trick.size() != 4) {
+            return Err("a deal has at most 13 tricks of 4 cards".to_string());
+        }
+        if self.table.size() >= 4 {
+            return Err("there are more than 3 cards on the table".to_string());
         }
+
+        let mut cards = CardSet::EMPTY;
+        let mut dealt = 0;
+        for deck in self.players.iter().chain(&self.tricks).chain([&self.table]) {
+            for card in deck.iter() {
+                cards.insert(card);
+                dealt += 1;
+            }
+        }
+        if dealt != 52 || cards.len() != 52 {
+            return Err("every card should be dealt exactly once".to_string());
+        }
+
+        // the players who played on the table have one card less than the others
+        let in_hand = 13 - self.tricks.len();
+        let played = self
+            .players
+            .iter()
+            .filter(|player| player.size() + 1 == in_hand)
+            .count();
+        if self
+            .players
+            .iter()
+            .any(|player| player.size() != in_hand && player.size() + 1 != in_hand)
+            || played != self.table.size()
+            || self.players[self.turn].size() != in_hand
+        {
+            return Err("the players hold the wrong amount of cards".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Put players in teams, e.g. `[0, 1, 0, 1]` for two teams of partners.
+    /// By default, every player is on its own.
+    pub fn set_teams(&mut self, teams: [usize; 4]) {
+        self.teams = teams;
     }
 
     pub fn add_human_players(&mut self, amount: usize) -> Result<usize, String> {
         if self.human_players + amount > 4 {
             return Err("Cannot have more than 4 players to this game...".to_string());
         }
+        if self.practice && self.human_players + amount > 1 {
+            return Err("Practice games have only one human player.".to_string());
+        }
 
         self.human_players += amount;
         Ok(self.human_players)
     }
 
-    pub fn new_deal(&mut self) {
-        let mut deck = Deck::new_full();
-        deck.shuffle();
+    /// In practice mode, the human player can take back their cards, see `undo`.
+    /// Only a game with one human player can be a practice game.
+    pub fn set_practice(&mut self, practice: bool) -> Result<(), String> {
+        if practice && self.human_players > 1 {
+            return Err("Only a game with one human player can be practiced.".to_string());
+        }
 
-        let tricks: Vec<Deck> = Vec::new();
-        self.tricks = tricks;
+        self.practice = practice;
+        if !practice {
+            self.history.clear();
+        }
+        Ok(())
+    }
+
+    pub fn is_practice(&self) -> bool {
+        self.practice
+    }
 
-        self.gone_cards = [[false; 13]; 4];
+    /// is there a card of the human player to take back?
+    pub fn can_undo(&self) -> bool {
+        self.practice && !self.history.is_empty()
+    }
+
+    /// remember the game as it is now, to come back to with `undo` or to `review`
+    pub(crate) fn remember(&mut self) {
+        let mut snapshot = self.clone();
+        snapshot.history.clear();
+        self.history.push(snapshot);
+    }
 
-        self.round_scores
+    /// Every card a human played this deal, with the game as it was just before.
+    ///
+    /// Cards played before the game was saved and resumed are not remembered.
+    pub fn human_decisions(&self) -> Vec<(&Game, Card)> {
+        self.history
             .iter()
-            .enumerate()
-            .for_each(|(plyr, score)| {
-                self.scores[plyr] += score;
-            });
+            .filter_map(|before| {
+                let trick = match self.tricks.get(before.tricks.len()) {
+                    Some(trick) => trick,
+                    None => &self.table,
+                };
+                trick
+                    .cards
+                    .get(before.table.size())
+                    .map(|card| (before, card.clone()))
+            })
+            .collect()
+    }
+
+    /// Take back the last card of the human player, and the cards played after it.
+    ///
+    /// Returns `false` when there is nothing to take back.
+    pub fn undo(&mut self) -> bool {
+        if !self.can_undo() {
+            return false;
+        }
 
+        let mut history = std::mem::take(&mut self.history);
+        *self = history.pop().expect("there is a game to go back to");
+        self.history = history;
+        true
+    }
+
+    pub fn new_deal(&mut self) {
+        self.new_deal_with(&mut rand::thread_rng());
+    }
+
+    /// deal new cards, shuffled with the given source of randomness
+    pub fn new_deal_with<R: rand::Rng + ?Sized>(&mut self, rng: &mut R) {
+        let mut deck = Deck::new_full();
+        deck.shuffle_with(rng);
+
+        // cards of an earlier deal can't be taken back
+        self.history.clear();
+
+        self.gone_cards = CardSet::EMPTY;
+        self.voids = [[false; 4]; 4];
+
+        // the next player deals
+        self.dealer = (self.dealer + 1) % 4;
+        self.tricks = Vec::new();
         self.round_scores = [0; 4];
 
         let cards = deck.pull_cards(13);
@@ -188,6 +364,8 @@ impl Game {
 
     pub fn trick(&mut self) -> Result<(), String> {
         if self.table.size() == 4 {
+            let leader = self.last_winner;
+
             // determine winning player, set turn to them
             self.turn = (self.winner() + self.last_winner) % 4;
             self.last_last_winner = self.last_winner;
@@ -202,17 +380,18 @@ impl Game {
 
             // keep track of cards that have been seen on the table (card counting)
             // & keep track of how many players can't follow in some type
-            new_trick.iter().for_each(|card| {
+            new_trick.iter().enumerate().for_each(|(i, card)| {
                 if let Some(fs) = first_suit {
                     // "following" cards (just not the first time)
                     if card.suit != fs {
                         self.nb_cant_follow[fs as usize] += 1;
+                        self.voids[(leader + i) % 4][fs as usize] = true;
                     }
                 } else {
                     first_suit = Some(card.suit);
                     self.nb_cant_follow[card.suit as usize] = 0;
                 }
-                self.gone_cards[card.suit as usize][(card.score() - 2) as usize] = true;
+                self.gone_cards.insert(card);
             });
 
             self.tricks.push(new_trick);
@@ -237,17 +416,9 @@ impl Game {
         }
     }
 
-    pub fn show_table(&self) {
-        show::show_table(&self.table, 0, self.last_winner);
-    }
-
-    pub fn show_table_wait(&self) {
-        show::show_table_wait(&self.table, 0, self.last_winner);
-    }
-
     pub fn player_plays(&mut self, card: usize) -> Result<(), String> {
-        let alowed = self.alowed_cards();
-        if !alowed.contains(&card) {
+        let hand = &self.players[self.turn];
+        if card >= hand.size() || !self.playable_set(self.turn).contains(hand.card(card)) {
             return Err("This player is not alowed to play this card.".to_string());
         }
 
@@ -259,27 +430,59 @@ impl Game {
     fn winner(&self) -> CardID {
         self.table
             .iter()
-            .cloned()
             .position_max_by(|card1, card2| card1.winning(card2, &self.trump))
             .unwrap()
     }
 
+    /// the cards `player` may play on the current table, as a set
+    fn playable_set(&self, player: PlayerID) -> CardSet {
+        let hand = self.players[player].set();
+
+        match self.table.cards.first() {
+            Some(first) if hand.has_suit(first.suit) => hand.of_suit(first.suit),
+            _ => hand,
+        }
+    }
+
+    /// the cards out of `cards` that win from the best card on the table, see `Card::winning`
+    fn better_set(&self, cards: CardSet) -> CardSet {
+        if self.table.is_empty() {
+            return cards;
+        }
+
+        let best_on_table = self.table.card(self.winner());
+        let higher = CardSet::higher_than(best_on_table);
+
+        if best_on_table.suit == self.trump {
+            cards & higher
+        } else {
+            // trumps and other suits than the one of the best card win as well
+            cards - (CardSet::suit_mask(best_on_table.suit) - higher)
+        }
+    }
+
+    /// the position of `card` in the hand of `player`
+    fn id_in_hand(&self, player: PlayerID, card: &Card) -> CardID {
+        self.players[player]
+            .id_of(card)
+            .expect("card should be in the hand of the player")
+    }
+
     /// returns a vector of alowed cards for this player, in this round
     pub fn alowed_cards(&self) -> Vec<usize> {
-        let player = &self.players[self.turn];
+        self.alowed_cards_of(self.turn)
+    }
 
-        if self.table.size() != 0 && player.can_follow(self.table.card(0).suit) {
-            let first_suit = self.table.card(0).suit;
-            // only return indices of cards of said suit
-            return player
-                .iter()
-                .enumerate()
-                .filter(|(_, card)| card.suit == first_suit)
-                .map(|(i, _)| i)
-                .collect_vec();
-        }
+    /// the cards `player` would be alowed to play on the current table
+    pub fn alowed_cards_of(&self, player: PlayerID) -> Vec<usize> {
+        let playable = self.playable_set(player);
 
-        (0..player.size()).collect_vec()
+        self.players[player]
+            .iter()
+            .enumerate()
+            .filter(|(_, card)| playable.contains(card))
+            .map(|(i, _)| i)
+            .collect_vec()
     }
 
     pub fn card_id_of(&self, player: PlayerID, card: &Card) -> Option<CardID> {
@@ -321,46 +524,47 @@ impl Game {
             .collect()
     }
 
-    fn play_easy(&mut self) {
+    pub(crate) fn play_easy(&mut self) {
+        self.player_plays(self.easy_card_id())
+            .expect("rule based player should be alowed to play its card");
+    }
+
+    /// the card the rule based AI would play
+    pub fn easy_card_id(&self) -> CardID {
         // get player id from the current turn
         let player = self.turn;
 
-        // get alowed indeces
-        let playable = self.alowed_cards();
-
-        if self.table.is_empty() {
-            return self
-                .player_plays(
-                    self.highest_card_of(player, &playable)
-                        .unwrap_or(playable[0]),
-                )
-                .expect("test");
-        }
+        let playable = self.playable_set(player);
 
-        let better_cards = self.better_cards_of(player, &playable);
+        let card = if self.table.is_empty() {
+            playable.highest(Suit::Hearts)
+        } else {
+            let better_cards = self.better_set(playable);
 
-        if !better_cards.is_empty() {
-            return self
-                .player_plays(
-                    self.lowest_card_of(player, &better_cards)
-                        .unwrap_or(better_cards[0]),
-                )
-                .expect("test");
-        }
+            match better_cards.is_empty() {
+                false => better_cards.lowest(Suit::Hearts),
+                // play other card
+                true => playable.lowest(Suit::Hearts),
+            }
+        };
 
-        // play other card
-        self.player_plays(
-            self.lowest_card_of(player, &playable)
-                .unwrap_or(playable[0]),
-        )
-        .expect("test");
+        let card = card.expect("a player with cards can always play one");
+        self.id_in_hand(player, &card)
     }
 
     fn print_boxed(input: &str, tab: usize) {
-        let width = input.len();
-        println!("{}╭{}╮", " ".repeat(tab), "─".repeat(width));
-        println!("{}│\x1b[1m{}\x1b[0m│", " ".repeat(tab), input);
-        println!("{}╰{}╯", " ".repeat(tab), "─".repeat(width));
+        let style = render::style();
+        let frame = style.frame();
+        let (tab, line) = (" ".repeat(tab), style.line(input.len()));
+
+        println!("{tab}{}{line}{}", frame.top_left, frame.top_right);
+        println!(
+            "{tab}{}{}{}",
+            frame.vertical,
+            style.bold(input),
+            frame.vertical
+        );
+        println!("{tab}{}{line}{}", frame.bottom_left, frame.bottom_right);
     }
 
     fn welcome() {
@@ -398,76 +602,156 @@ impl Game {
         show::wait_q();
     }
 
-    fn input_instructions(&self) {
-        println!("Press the [arrow keys] (or vim keys) to select a card.");
-        println!("Press [enter] or [space bar] to play that card.");
+    fn input_instructions(&self, ui: &mut dyn Ui) {
+        ui.say("Press the [arrow keys] (or vim keys) to select a card.");
+        ui.say("Press [enter] or [space bar] to play that card.");
+        ui.say("Press [?] for a hint.");
+        if self.practice {
+            ui.say("Press [u] to take back your last card.");
+        }
+        ui.say("Press [q] to save the game and quit.");
     }
 
-    fn ask_card(&mut self) {
-        let stdin = stdin();
-        let mut stdout = stdout().into_raw_mode().unwrap();
-
-        write!(stdout, "{}", termion::cursor::Hide).unwrap();
-
-        stdout.flush().unwrap();
-
+    /// let the human select a card, or choose to take one back or quit
+    fn ask_card(&mut self, ui: &mut dyn Ui, q: &Option<Q<GameState>>) -> Choice {
         let mut wrong_count = 0;
-        for c in stdin.keys() {
-            {
-                match c.unwrap() {
-                    Key::Char('h') | Key::Left => {
-                        wrong_count = 0;
-                        self.players[self.turn].select_left()
-                    }
-                    Key::Char('j') | Key::Down => {
-                        wrong_count = 0;
-                        self.players[self.turn].select_down()
-                    }
-                    Key::Char('k') | Key::Up => {
-                        wrong_count = 0;
-                        self.players[self.turn].select_up()
-                    }
-                    Key::Char('l') | Key::Right => {
+        loop {
+            match ui.key() {
+                Key::Char('h') | Key::Left => self.players[self.turn].select_left(),
+                Key::Char('j') | Key::Down => self.players[self.turn].select_down(),
+                Key::Char('k') | Key::Up => self.players[self.turn].select_up(),
+                Key::Char('l') | Key::Right => self.players[self.turn].select_right(),
+                Key::Char(' ') | Key::Char('\n') => return Choice::Play,
+                Key::Char('?') => self.show_hint(ui, q),
+                Key::Char('u') if self.practice => return Choice::Undo,
+                Key::Char('q') | Key::Ctrl('c') => return Choice::Quit,
+                _ => {
+                    wrong_count += 1;
+                    if wrong_count > 1 {
+                        self.input_instructions(ui);
                         wrong_count = 0;
-                        self.players[self.turn].select_right()
-                    }
-                    Key::Char(' ') | Key::Char('\n') => break,
-                    Key::Char('q') => {
-                        stdout.flush().unwrap();
-                        write!(stdout, "{}", termion::cursor::Show).unwrap();
-                        drop(stdout);
-                        exit(0);
-                    }
-                    _ => {
-                        wrong_count += 1;
                     }
+                    continue;
                 }
             }
-            stdout.flush().unwrap();
-            stdout.suspend_raw_mode().unwrap();
 
-            if wrong_count > 1 {
-                self.input_instructions();
-            } else {
-                self.show_player_state();
+            wrong_count = 0;
+            self.paint(ui);
+        }
+    }
+
+    /// what the AI would play and what the cards are worth, for a human asking for a hint
+    fn show_hint(&self, ui: &mut dyn Ui, q: &Option<Q<GameState>>) {
+        let style = render::style();
+        let hand = &self.players[self.turn];
+        let state: GameState = self.state();
+        let mut lines = Vec::new();
+
+        let values = q.as_ref().and_then(|q| q.get(&state));
+        match (q, values) {
+            (None, _) => {
+                lines.push("There is no model to ask, the opponents are rule based.".to_string())
+            }
+            (Some(_), None) => {
+                lines.push("The model has never been in this situation.".to_string())
+            }
+            (Some(_), Some(values)) => {
+                let mut actions: Vec<(Action, f64)> = MultiAgentSpace::actions(self)
+                    .into_iter()
+                    .filter_map(|action| values.get(&action).map(|value| (action, *value)))
+                    .collect();
+                actions.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+                lines.push(style.bold("The model would"));
+                for (action, value) in actions {
+                    let card = hand.card(self.action_card_id(&action));
+                    lines.push(format!("  {:<20} {card}  {value:.3}", action.to_string()));
+                }
             }
+        }
 
-            stdout.activate_raw_mode().unwrap();
+        ui.say(&format!(
+            "Playing out every card {} times...",
+            search::HINT_ROLLOUTS
+        ));
+        let estimates = search::estimate_cards(self, search::HINT_ROLLOUTS);
+        lines.push(String::new());
+        lines.push(style.bold(format!(
+            "Tricks you can expect, over {} playouts",
+            search::HINT_ROLLOUTS
+        )));
+        for estimate in estimates {
+            lines.push(format!(
+                "  {}  {:.2}",
+                hand.card(estimate.card),
+                estimate.tricks
+            ));
         }
 
-        write!(stdout, "{}", termion::cursor::Show).unwrap();
+        ui.page("Hint", lines);
     }
 
-    pub fn show_player_state(&mut self) {
-        show::show_table(&self.table, self.turn, self.last_winner);
-        show::show_last_non_empty(&self.tricks, self.last_last_winner, self.last_winner);
-        println!("Your hand: [Player {}]", self.turn);
-        self.players[self.turn].show_cards();
+    /// the name of `player` on the screen
+    fn name(&self, player: PlayerID) -> String {
+        match player {
+            0 => "You".to_string(),
+            _ => format!("Player {player}"),
+        }
+    }
+
+    /// Fill in the screen with the game, as the human whose turn it is sees it, and draw it.
+    pub fn paint(&self, ui: &mut dyn Ui) {
+        let style = render::style();
+        // the first human, when it is not a human's turn
+        let me = match self.turn < self.human_players {
+            true => self.turn,
+            false => 0,
+        };
+
+        let screen = ui.screen();
+        screen.table = self.table.table_lines(me, self.last_winner, 5).to_vec();
+        screen.last_trick = match self.tricks.last() {
+            Some(trick) => trick
+                .table_lines(me, self.last_last_winner, self.last_winner)
+                .to_vec(),
+            None => vec!["No tricks yet.".to_string()],
+        };
+        screen.hand = style
+            .hand(&self.players[me])
+            .lines()
+            .map(String::from)
+            .collect();
+
+        screen.scores = vec![style.dim(format!("{:<10}{:>7}{:>7}", "", "deal", "total"))];
+        for (player, total) in self.scoreboard.totals().into_iter().enumerate() {
+            let line = format!(
+                "{:<10}{:>7}{:>7}",
+                self.name(player),
+                self.round_scores[player],
+                total
+            );
+            screen.scores.push(match player == self.turn {
+                true => style.bold(line),
+                false => line,
+            });
+        }
+
+        screen.auction = vec![
+            format!("Dealer  {}", self.name(self.dealer)),
+            format!("Trump   {}", style.suit(self.trump)),
+            format!("Turn    {}", self.name(self.turn)),
+        ];
+
+        ui.draw();
     }
 
     fn alowed_or_why_not(&self, card: CardID) -> Result<(), String> {
         let player = &self.players[self.turn];
 
+        if card >= player.size() {
+            return Err("Select a card first, with the arrow keys.".to_string());
+        }
+
         if self.table.size() != 0 && player.can_follow(self.table.card(0).suit) {
             let first_suit = self.table.card(0).suit;
             if player.card(card).suit != first_suit {
@@ -483,28 +767,39 @@ impl Game {
         Ok(())
     }
 
-    fn human_plays(&mut self) {
-        // showing what the game looks like atm
-        self.show_player_state();
-        show::wait();
+    /// returns `false` when the human quit instead of playing
+    fn human_plays(&mut self, ui: &mut dyn Ui, q: &Option<Q<GameState>>) -> bool {
+        self.paint(ui);
 
         // ask what card to play and check validity
         let card_id: CardID = {
             loop {
                 // loop until correct card given
-                self.ask_card();
+                match self.ask_card(ui, q) {
+                    Choice::Play => {}
+                    Choice::Undo => {
+                        match self.undo() {
+                            true => self.paint(ui),
+                            false => ui.say("There is no card to take back."),
+                        }
+                        continue;
+                    }
+                    Choice::Quit => return false,
+                }
 
                 let i = self.players[self.turn].selected_id();
 
                 match self.alowed_or_why_not(i) {
                     Ok(_) => break i,
-                    Err(err) => println!("{}", err),
+                    Err(err) => ui.say(&err),
                 }
             }
         };
 
+        self.remember();
         self.player_plays(card_id)
             .expect("human player should be alowed to play selected card");
+        true
     }
 
     pub fn best_card_id_ai(&self, q: &Q<GameState>) -> usize {
@@ -532,90 +827,344 @@ impl Game {
         self.play_easy();
     }
 
-    fn bidding(&mut self) {
-        show::dealer(self.dealer);
+    fn bidding(&mut self, ui: &mut dyn Ui) {
+        ui.say(&format!(
+            "The current dealer is {}.",
+            self.name(self.dealer)
+        ));
 
         // TODO: implement bidding
     }
 
-    fn play_rounds(&mut self, q: &Option<Q<GameState>>) {
-        for _ in 0..13 {
-            // play one round
-            for _ in 0..4 {
+    /// play the rest of the deal, returns `false` when a human quit halfway
+    fn play_rounds(&mut self, ui: &mut dyn Ui, q: &Option<Q<GameState>>) -> bool {
+        while !self.deal_over() {
+            // play (the rest of) one round
+            while self.table.size() < 4 {
                 if self.turn < self.human_players {
-                    self.human_plays();
-                    self.show_table_wait();
-                } else {
-                    if let Some(q) = q {
-                        self.ai_plays(q);
-                    } else {
-                        self.rulebased_plays();
+                    if !self.human_plays(ui, q) {
+                        return false;
                     }
-                    self.show_table_wait();
+                } else if let Some(q) = q {
+                    self.ai_plays(q);
+                } else {
+                    self.rulebased_plays();
                 }
+                self.paint(ui);
+                ui.pause(500);
             }
 
             self.trick().expect("Couldn't play trick in play_round");
 
-            show::winner(self.turn);
-            show::wait();
+            ui.say(&format!("Winner this round: {}", self.name(self.turn)));
+            self.paint(ui);
+            ui.pause(500);
         }
+
+        true
     }
 
-    pub fn play_deal(&mut self, q: &Option<Q<GameState>>) {
-        // bidding
-        self.bidding();
+    /// Play the deal, or what is left of it, on `ui`.
+    ///
+    /// Returns `false` when a human player quit halfway,
+    /// the deal is resumed by calling this again (e.g. after saving the game in a `Session`).
+    pub fn play_deal(&mut self, ui: &mut dyn Ui, q: &Option<Q<GameState>>) -> bool {
+        // bidding, unless the deal has started already
+        if self.tricks.is_empty() && self.table.is_empty() {
+            self.bidding(ui);
+        }
 
         // play the actual rounds
-        self.play_rounds(q);
+        self.paint(ui);
+        if !self.play_rounds(ui, q) {
+            return false;
+        }
+
+        // the deal is over, it goes on the scoreboard
+        self.record_deal();
+        self.offer_review(ui, q);
+        true
     }
 
-    pub fn agent_plays_round(&mut self, card: CardID, q: &Option<&Q<GameState>>) {
+    /// ask the human whether they want to see the mistakes they made this deal
+    fn offer_review(&self, ui: &mut dyn Ui, q: &Option<Q<GameState>>) {
+        if self.history.is_empty() || !ui.ask("Review your cards of this deal?", false) {
+            return;
+        }
+
+        ui.say(&format!(
+            "Playing out every card {} times...",
+            review::REVIEW_ROLLOUTS
+        ));
+        let decisions = review::review(self, q.as_ref(), review::REVIEW_ROLLOUTS);
+        ui.page("Review", show::review(&decisions));
+    }
+
+    /// let the opponents of player 0 play, using `q` or the rule based AI
+    fn opponent_plays(&mut self, q: &Option<&Q<GameState>>) {
+        if let Some(q) = q {
+            self.ai_plays(q);
+        } else {
+            self.rulebased_plays();
+        }
+    }
+
+    /// let the opponents put their cards down, until player 0 is up
+    fn opponents_play_until_agent(&mut self, q: &Option<&Q<GameState>>) {
+        while self.turn != 0 {
+            self.opponent_plays(q);
+        }
+    }
+
+    /// The agent (player 0) plays `card`, after which the trick is completed.
+    ///
+    /// When the deal isn't over yet, the opponents lead until it is the agent's turn again.
+    /// Unlike `agent_plays_round`, this never starts a new deal.
+    pub fn agent_plays_trick(&mut self, card: CardID, q: &Option<&Q<GameState>>) {
         self.player_plays(card)
             .expect("agent should be alowed to play selected card");
 
         // let opponent play until the current trick is full
-        loop {
-            if self.table.size() == 4 {
-                break;
-            }
-
-            if let Some(q) = q {
-                self.ai_plays(q);
-            } else {
-                self.rulebased_plays();
-            }
+        while self.table.size() != 4 {
+            self.opponent_plays(q);
         }
 
         // complete the trick
         self.trick()
-            .expect("Should finish trick in agent_plays_round");
+            .expect("Should finish trick in agent_plays_trick");
+
+        if !self.deal_over() {
+            self.opponents_play_until_agent(q);
+        }
+    }
+
+    /// Same as `agent_plays_trick`, but starts a new deal when this one is over.
+    pub fn agent_plays_round(&mut self, card: CardID, q: &Option<&Q<GameState>>) {
+        self.agent_plays_trick(card, q);
 
         // start a new round if necessary
-        if self.tricks.len() == 13 {
+        if self.deal_over() {
             self.new_deal();
+            self.opponents_play_until_agent(q);
         }
+    }
 
-        // let first opponents put their cards down, until player 0 is up
-        loop {
-            if self.turn == 0 {
-                break;
-            }
+    /// the state of the game, as `player` sees it
+    pub fn state_of(&self, player: PlayerID) -> GameState {
+        let can_follow: bool = self.can_follow(player);
 
-            if let Some(q) = q {
-                self.ai_plays(q);
-            } else {
-                self.rulebased_plays();
+        let mut has_highest = [true; 4];
+        let mut first_suit = -1;
+        let mut have_higher = true;
+        let have_trump = self.players[player].can_follow(Suit::Hearts);
+
+        let nb_cards = 0; //self.players[player].size();
+
+        if !self.first() {
+            let first_card_suit = self.table.card(0).suit;
+            first_suit = first_card_suit as i8;
+
+            // determine whether I can go higher than the current winner
+            have_higher = !self.better_set(self.playable_set(player)).is_empty();
+        }
+
+        let hand = self.players[player].set();
+        for s in Suit::iterator() {
+            // every card higher than my highest one has to be gone
+            has_highest[*s as usize] = match hand.highest_of_suit(*s) {
+                Some(my_highest) => CardSet::higher_than(&my_highest).is_subset(self.gone_cards),
+                None => false,
+            };
+        }
+
+        // compress the four 8bit numbers to four concatenated 2 bit numbers
+        // saves 30% on serialized model size!!
+        let mut nb_out_of: u8 = 0b00000000;
+        self.nb_cant_follow.iter().enumerate().for_each(|(i, nb)| {
+            // bitwise or with shifted nb
+            nb_out_of |= *nb << (2 * i);
+        });
+
+        GameState {
+            can_follow,
+            has_highest,
+            first_suit,
+            have_higher,
+            have_trump,
+            nb_cards,
+            nb_out_of,
+        }
+    }
+
+    /// have all thirteen tricks of this deal been played?
+    pub fn deal_over(&self) -> bool {
+        self.tricks.len() == 13
+    }
+
+    /// Let an agent using `q` play `deals` deals as player 0, against rule based opponents.
+    ///
+    /// Returns the average amount of tricks the agent won per deal.
+    pub fn evaluate(q: &Q<GameState>, deals: usize) -> f64 {
+        Self::evaluate_seeded(q, deals, rand::random())
+    }
+
+    /// Like `evaluate`, but the deals are the same for the same `seed`,
+    /// so the scores of different models (or moments in a training) can be compared.
+    pub fn evaluate_seeded(q: &Q<GameState>, deals: usize, seed: u64) -> f64 {
+        if deals == 0 {
+            return 0.0;
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut game = Game::new();
+        game.new_deal_with(&mut rng);
+        let mut tricks: u32 = 0;
+
+        for _ in 0..deals {
+            game.opponents_play_until_agent(&None);
+
+            while !game.deal_over() {
+                let best_card_id = game.best_card_id_ai(q);
+                game.agent_plays_trick(best_card_id, &None);
             }
+
+            tricks += game.round_scores[0];
+            game.new_deal_with(&mut rng);
         }
+
+        tricks as f64 / deals as f64
+    }
+
+    /// put the deal on the scoreboard, every trick is worth a point
+    fn record_deal(&mut self) {
+        self.scoreboard.record(DealRecord {
+            dealer: self.dealer,
+            contract: None,
+            declarers: Vec::new(),
+            tricks: self.round_scores,
+            points: self.round_scores,
+        });
+    }
+
+    /// every deal that was played to the end with `play_deal`
+    pub fn scoreboard(&self) -> &Scoreboard {
+        &self.scoreboard
     }
 
     pub fn show_scores(&self) {
-        println!("The scores: {:?}", self.scores);
+        for line in self.scoreboard.lines() {
+            println!("{line}");
+        }
+    }
+
+    /// the amount of tricks that are complete in this deal
+    pub fn tricks_played(&self) -> usize {
+        self.tricks.len()
+    }
+
+    /// the amount of tricks `player` has won in this deal
+    pub fn tricks_of(&self, player: PlayerID) -> u32 {
+        self.round_scores[player]
+    }
+
+    /// The game as `player` could imagine it: the cards they can't see are dealt
+    /// to the other players again, who keep the amount of cards they have
+    /// and get no cards of the suits they have shown not to have.
+    pub(crate) fn redeal_hidden<R: rand::Rng + ?Sized>(
+        &self,
+        player: PlayerID,
+        rng: &mut R,
+    ) -> Game {
+        let mut game = self.clone();
+        game.history.clear();
+
+        // the voids of earlier tricks, and of the cards on the table
+        let mut voids = self.voids;
+        if let Some(first) = self.table.iter().next() {
+            for (i, card) in self.table.iter().enumerate().skip(1) {
+                if card.suit != first.suit {
+                    voids[(self.last_winner + i) % 4][first.suit as usize] = true;
+                }
+            }
+        }
+
+        let others = (0..4).filter(|other| *other != player).collect_vec();
+        let mut hidden = Deck::new_from(
+            others
+                .iter()
+                .flat_map(|other| self.players[*other].iter().cloned())
+                .collect(),
+        );
+        hidden.shuffle_with(rng);
+
+        let mut room = [0; 4];
+        let mut left = [0; 4];
+        for other in &others {
+            room[*other] = self.players[*other].size();
+            game.players[*other] = Deck::new_empty();
+        }
+        for card in hidden.iter() {
+            left[card.suit as usize] += 1;
+        }
+
+        // every card goes to a player who can still take the rest of the cards,
+        // which the actual hands show is always possible
+        for card in hidden.iter() {
+            let suit = card.suit as usize;
+            left[suit] -= 1;
+            let candidates = others
+                .iter()
+                .copied()
+                .filter(|other| room[*other] > 0 && !voids[*other][suit])
+                .filter(|other| {
+                    let mut room = room;
+                    room[*other] -= 1;
+                    Self::can_deal(&left, &room, &voids)
+                })
+                .collect_vec();
+            let other = *candidates
+                .choose_weighted(rng, |other| room[*other])
+                .expect("the actual hands can be dealt");
+            room[other] -= 1;
+            game.players[other].add(card.clone());
+        }
+
+        for other in others {
+            game.players[other].sort();
+        }
+
+        game
+    }
+
+    /// Can `left` cards of every suit be dealt to players with `room` for them,
+    /// without giving anyone a suit they are void in?
+    fn can_deal(left: &[usize; 4], room: &[usize; 4], voids: &[[bool; 4]; 4]) -> bool {
+        // every group of suits needs enough room with the players who can take one of them
+        (1..16usize).all(|suits| {
+            let in_group = |suit: usize| suits & (1 << suit) != 0;
+            let needed: usize = (0..4).filter(|s| in_group(*s)).map(|s| left[s]).sum();
+            let available: usize = (0..4)
+                .filter(|player| (0..4).any(|s| in_group(s) && !voids[*player][s]))
+                .map(|player| room[player])
+                .sum();
+            needed <= available
+        })
+    }
+
+    /// the rule based AI plays the rest of the deal, for every player
+    pub(crate) fn play_out(&mut self) {
+        while !self.deal_over() {
+            while self.table.size() < 4 {
+                self.play_easy();
+            }
+            self.trick().expect("a full table can be tricked");
+        }
     }
 
+    /// the points of the first player on the scoreboard,
+    /// deals that are not played with `play_deal` are not counted
     pub fn get_ai_score(&self) -> u32 {
-        self.scores[0]
+        self.scoreboard.totals()[0]
     }
 
     pub fn can_follow(&self, player: PlayerID) -> bool {
@@ -635,7 +1184,7 @@ impl Game {
     }
 
     pub fn show_gone(&self) {
-        println!("Gone Cards:\n{:?}", self.gone_cards);
+        println!("Gone Cards:\n{}", self.gone_cards);
     }
 
     /// returns the ID of the card that corresponds with this action
@@ -643,54 +1192,35 @@ impl Game {
     pub fn action_card_id(&self, action: &Action) -> CardID {
         let player = self.turn;
 
-        let playable = self.alowed_cards();
+        let playable = self.playable_set(player);
+        // the cards of a suit, or any card when there are none of that suit
+        let of_suit = |suit: Suit| match playable.of_suit(suit) {
+            cards if cards.is_empty() => playable,
+            cards => cards,
+        };
 
-        match action {
-            Action::PlayWorst(suit) => {
-                let mut suit_cards = self.of_which_suit(player, &playable, *suit as usize);
-                if suit_cards.is_empty() {
-                    suit_cards = playable.clone();
-                }
-                self.lowest_card_of(player, &suit_cards)
-                    .unwrap_or(playable[0])
-            }
-            Action::RaiseLow => {
-                let better = self.better_cards_of(player, &playable);
-                self.lowest_card_of(player, &better)
-                    .unwrap_or_else(|| playable[0])
-            }
-            Action::RaiseHigh => {
-                let better = self.better_cards_of(player, &playable);
-                self.highest_card_of(player, &better)
-                    .unwrap_or_else(|| playable[0])
-            }
-            Action::TrumpHigh => {
-                let trumps = self.of_which_suit(player, &playable, 3);
-                self.highest_card_of(player, &trumps)
-                    .unwrap_or_else(|| playable[0])
-            }
-            Action::TrumpLow => {
-                let trumps = self.of_which_suit(player, &playable, 3);
-                self.lowest_card_of(player, &trumps)
-                    .unwrap_or_else(|| playable[0])
-            }
-            Action::PlayBest(suit) => {
-                let mut suit_cards = self.of_which_suit(player, &playable, *suit as usize);
-                if suit_cards.is_empty() {
-                    suit_cards = playable.clone();
-                }
-                self.highest_card_of(player, &suit_cards)
-                    .unwrap_or_else(|| playable[0])
-            }
+        let card = match action {
+            Action::PlayWorst(suit) => of_suit(*suit).lowest(Suit::Hearts),
+            Action::RaiseLow => self.better_set(playable).lowest(Suit::Hearts),
+            Action::RaiseHigh => self.better_set(playable).highest(Suit::Hearts),
+            Action::TrumpHigh => playable.of_suit(Suit::Hearts).highest(Suit::Hearts),
+            Action::TrumpLow => playable.of_suit(Suit::Hearts).lowest(Suit::Hearts),
+            Action::PlayBest(suit) => of_suit(*suit).highest(Suit::Hearts),
             Action::ComeBest => {
                 let state: GameState = self.state();
                 let suit = state.has_highest.iter().position_max().unwrap();
-                let suit_ids = self.of_which_suit(player, &playable, suit);
 
-                self.highest_card_of(player, &suit_ids)
-                    .unwrap_or_else(|| playable[0])
+                playable
+                    .of_suit(*Suit::iterator().nth(suit).unwrap())
+                    .highest(Suit::Hearts)
             }
-        }
+        };
+
+        // the first playable card, when the action has no card of its own
+        let card = card
+            .or_else(|| playable.first())
+            .expect("player should have cards");
+        self.id_in_hand(player, &card)
     }
 }
 
@@ -710,8 +1240,7 @@ impl GameSpace<GameState> for Game {
     fn actions(&self) -> Vec<Action> {
         let mut alowed: Vec<Action> = Vec::with_capacity(5);
         let player = self.turn;
-        let playable = self.alowed_cards();
-        let better = self.better_cards_of(player, &playable);
+        let better = self.better_set(self.playable_set(player));
         let state: GameState = self.state();
         let first: bool = state.first_suit == -1;
         let can_follow: bool = self.can_follow(player);
@@ -738,71 +1267,77 @@ impl GameSpace<GameState> for Game {
     }
 
     fn state(&self) -> GameState {
-        let player = self.turn;
+        self.state_of(self.turn)
+    }
 
-        let can_follow: bool = self.can_follow(player);
+    fn take_action(&mut self, action: &Action, q: &Option<&Q<GameState>>) {
+        let card_id = self.action_card_id(action);
+        self.agent_plays_trick(card_id, q);
+    }
 
-        let mut has_highest = [true; 4];
-        let mut first_suit = -1;
-        let mut have_higher = true;
-        let have_trump = self.players[player].can_follow(Suit::Hearts);
+    /// one deal is one episode
+    fn is_terminal(&self) -> bool {
+        self.deal_over()
+    }
 
-        let nb_cards = 0; //self.players[player].size();
+    fn reset(&mut self, q: &Option<&Q<GameState>>) {
+        self.new_deal();
+        self.opponents_play_until_agent(q);
+    }
+}
 
-        if !self.first() {
-            let first_card_suit = self.table.card(0).suit;
-            first_suit = first_card_suit as i8;
+/// All four players can learn, a trick rewards every player of the winner's team.
+impl MultiAgentSpace<GameState> for Game {
+    fn new_space(&self) -> Box<dyn MultiAgentSpace<GameState>> {
+        let mut game = Self::new();
+        game.teams = self.teams;
+        Box::new(game)
+    }
 
-            // determine whether I can go higher than the current winner
-            let playable = self.alowed_cards();
+    fn seats(&self) -> usize {
+        4
+    }
 
-            let winner = self.winner();
+    fn to_act(&self) -> usize {
+        self.turn
+    }
 
-            have_higher = playable.iter().any(|card_id| {
-                self.players[player]
-                    .card(*card_id)
-                    .winning(&self.table.cards[winner], &self.trump)
-                    == Ordering::Greater
-            });
-        }
+    fn observe(&self, seat: usize) -> GameState {
+        self.state_of(seat)
+    }
 
-        for s in Suit::iterator() {
-            let ai_suit_deck = self.players[player].get_deck_of_suit(s);
+    fn actions(&self) -> Vec<Action> {
+        <Self as GameSpace<GameState>>::actions(self)
+    }
 
-            if let Some(my_highest) = ai_suit_deck.iter().max() {
-                for i in (my_highest.score() + 1)..15 {
-                    if !self.gone_cards[*s as usize][(i - 2) as usize] {
-                        has_highest[*s as usize] = false;
-                        break;
-                    }
-                }
-            } else {
-                has_highest[*s as usize] = false;
-            }
-        }
+    fn step(&mut self, action: &Action) -> Vec<f64> {
+        let card_id = self.action_card_id(action);
+        self.player_plays(card_id)
+            .expect("seat should be alowed to play the card of its action");
 
-        // compress the four 8bit numbers to four concatenated 2 bit numbers
-        // saves 30% on serialized model size!!
-        let mut nb_out_of: u8 = 0b00000000;
-        self.nb_cant_follow.iter().enumerate().for_each(|(i, nb)| {
-            // bitwise or with shifted nb
-            nb_out_of |= *nb << (2 * i);
-        });
+        let mut rewards = vec![0.0; 4];
 
-        GameState {
-            can_follow,
-            has_highest,
-            first_suit,
-            have_higher,
-            have_trump,
-            nb_cards,
-            nb_out_of,
+        if self.table.size() == 4 {
+            self.trick().expect("Should finish a full trick");
+
+            // the winner of the trick is up next
+            let team = self.teams[self.turn];
+            rewards
+                .iter_mut()
+                .zip(self.teams)
+                .filter(|(_, t)| *t == team)
+                .for_each(|(reward, _)| *reward = 1.0);
         }
+
+        rewards
     }
 
-    fn take_action(&mut self, action: &Action, q: &Option<&Q<GameState>>) {
-        let card_id = self.action_card_id(action);
-        self.agent_plays_round(card_id, q);
+    fn is_terminal(&self) -> bool {
+        self.deal_over()
+    }
+
+    fn reset(&mut self) {
+        self.new_deal();
     }
 }
 
@@ -847,6 +1382,334 @@ mod tests {
         Game::new()
     }
 
+    #[test]
+    fn multi_agent_trick_rewards_team() {
+        let mut game = init_game();
+        game.set_teams([0, 1, 0, 1]);
+
+        let mut rewards = vec![0.0; 4];
+        for _ in 0..4 {
+            let action = MultiAgentSpace::actions(&game)[0];
+            rewards = game.step(&action);
+        }
+
+        let winner = game.to_act();
+        assert_eq!(rewards[winner], 1.0);
+        assert_eq!(rewards[(winner + 2) % 4], 1.0);
+        assert_eq!(rewards.iter().sum::<f64>(), 2.0);
+    }
+
+    #[test]
+    fn multi_agent_training_learns_every_seat() {
+        let mut learner: fortify::QLearner<GameState> = fortify::QLearner::new_with_iter(5000);
+        learner.set_threads(2);
+        learner.disable_verbose();
+
+        learner.train_multi_agent(&init_game(), fortify::TableSharing::Separate);
+
+        assert!((0..4).all(|seat| !learner.get_seat_q(seat).is_empty()));
+        assert_eq!(learner.get_seat_q(0), learner.get_q());
+    }
+
+    #[test]
+    fn multi_agent_training_continues_from_a_checkpoint() {
+        let mut learner: fortify::QLearner<GameState> = fortify::QLearner::new_with_iter(2000);
+        learner.set_threads(2);
+        learner.disable_verbose();
+        learner.replay = Some(fortify::Replay {
+            capacity: 4000,
+            sampling: fortify::Sampling::Uniform,
+            ratio: 2,
+        });
+
+        learner.train_multi_agent(&init_game(), fortify::TableSharing::Separate);
+        let checkpoint = learner.checkpoint();
+        assert_eq!(checkpoint.seat_tables.len(), 4);
+
+        let mut resumed = fortify::QLearner::from_checkpoint(checkpoint);
+        resumed.set_iterations(4000);
+        resumed.set_threads(2);
+        resumed.disable_verbose();
+        let visits = resumed.get_visits();
+        resumed.train_multi_agent(&init_game(), fortify::TableSharing::Separate);
+
+        // every seat went on from where it was, including its visit counts
+        assert!((0..4).all(|seat| {
+            let before = learner.get_seat_q(seat);
+            let after = resumed.get_seat_q(seat);
+            before.keys().all(|state| after.contains_key(state))
+        }));
+        let count = |visits: &fortify::Visits<GameState>| -> u64 {
+            visits.values().flat_map(|actions| actions.values()).sum()
+        };
+        assert!(count(&resumed.get_visits()) > count(&visits));
+    }
+
+    /// the card of an action, chosen with the card id based helpers
+    fn action_card_id_of_ids(game: &Game, action: &Action) -> CardID {
+        let player = game.turn;
+        let playable = game.alowed_cards();
+        let of_suit = |suit: usize| match game.of_which_suit(player, &playable, suit) {
+            cards if cards.is_empty() => playable.clone(),
+            cards => cards,
+        };
+        let better = game.better_cards_of(player, &playable);
+        let trumps = game.of_which_suit(player, &playable, 3);
+
+        let card = match action {
+            Action::PlayWorst(suit) => game.lowest_card_of(player, &of_suit(*suit as usize)),
+            Action::RaiseLow => game.lowest_card_of(player, &better),
+            Action::RaiseHigh => game.highest_card_of(player, &better),
+            Action::TrumpHigh => game.highest_card_of(player, &trumps),
+            Action::TrumpLow => game.lowest_card_of(player, &trumps),
+            Action::PlayBest(suit) => game.highest_card_of(player, &of_suit(*suit as usize)),
+            Action::ComeBest => {
+                let suit = game
+                    .state_of(player)
+                    .has_highest
+                    .iter()
+                    .position_max()
+                    .unwrap();
+                game.highest_card_of(player, &game.of_which_suit(player, &playable, suit))
+            }
+        };
+
+        card.unwrap_or(playable[0])
+    }
+
+    #[test]
+    fn card_sets_choose_the_same_cards() {
+        let mut game = init_game();
+
+        for deal in 0..50 {
+            game.new_deal();
+
+            let mut turn = deal;
+            while !MultiAgentSpace::is_terminal(&game) {
+                let actions = MultiAgentSpace::actions(&game);
+                for action in &actions {
+                    assert_eq!(
+                        game.action_card_id(action),
+                        action_card_id_of_ids(&game, action),
+                        "{action} chooses a different card"
+                    );
+                }
+
+                turn += 1;
+                game.step(&actions[turn % actions.len()]);
+            }
+        }
+    }
+
+    #[test]
+    fn json_snapshot_mid_deal() {
+        let mut game = init_game();
+        game.new_deal_with(&mut ChaCha8Rng::seed_from_u64(7));
+
+        // one full trick, and two cards of the next one
+        for _ in 0..6 {
+            let action = MultiAgentSpace::actions(&game)[0];
+            game.step(&action);
+        }
+
+        let json = game.to_json().unwrap();
+        let resumed = Game::from_json(&json).unwrap();
+        assert!(resumed == game, "the game changed in json:\n{json}");
+        assert_eq!(resumed.table.size(), 2);
+        assert_eq!(resumed.gone_cards.len(), 4);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let fields: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str())
+            .collect();
+        for field in [
+            "tricks",
+            "table",
+            "players",
+            "turn",
+            "trump",
+            "scoreboard",
+            "gone_cards",
+        ] {
+            assert!(fields.contains(&field), "{field} is missing");
+        }
+        assert_eq!(value["trump"], "Hearts");
+        assert_eq!(value["tricks"][0].as_array().unwrap().len(), 4);
+        assert_eq!(value["players"][0].as_array().unwrap().len(), 12);
+        assert!(value["players"][0][0]
+            .as_str()
+            .unwrap()
+            .parse::<Card>()
+            .is_ok());
+    }
+
+    /// a ui that nobody looks at, pressing keys that play the cards one after another
+    #[derive(Default)]
+    struct Scripted {
+        screen: crate::tui::Screen,
+        pressed: usize,
+    }
+
+    impl Ui for Scripted {
+        fn screen(&mut self) -> &mut crate::tui::Screen {
+            &mut self.screen
+        }
+        fn draw(&mut self) {}
+        fn say(&mut self, _message: &str) {}
+        fn key(&mut self) -> Key {
+            self.pressed += 1;
+            match self.pressed {
+                // stuck on a card that can't be played
+                10_000.. => Key::Char('q'),
+                n if n % 2 == 0 => Key::Char('\n'),
+                n if n % 26 == 1 => Key::Char('j'),
+                _ => Key::Char('l'),
+            }
+        }
+        fn pause(&mut self, _millis: u64) {}
+        fn page(&mut self, _title: &str, _lines: Vec<String>) {}
+    }
+
+    #[test]
+    fn deals_are_played_on_any_ui() {
+        let mut game = init_game();
+        game.add_human_players(1).unwrap();
+
+        assert!(game.play_deal(&mut Scripted::default(), &None));
+        assert!(game.deal_over());
+    }
+
+    #[test]
+    fn redealt_hands_keep_the_shown_voids() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let mut game = init_game();
+        game.new_deal_with(&mut rng);
+
+        // play until someone couldn't follow
+        while !game.voids.iter().flatten().any(|void| *void) {
+            assert!(!game.deal_over(), "everyone could always follow");
+            let action = MultiAgentSpace::actions(&game)[0];
+            game.step(&action);
+        }
+
+        let player = game.to_act();
+        for _ in 0..50 {
+            let redealt = game.redeal_hidden(player, &mut rng);
+            for other in 0..4 {
+                assert_eq!(redealt.players[other].size(), game.players[other].size());
+                for suit in [Suit::Spades, Suit::Clubs, Suit::Diamonds, Suit::Hearts] {
+                    if game.voids[other][suit as usize] {
+                        assert!(!redealt.players[other].has_suit(&suit));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn json_games_that_dont_hold_together_are_rejected() {
+        let mut game = init_game();
+        game.new_deal_with(&mut ChaCha8Rng::seed_from_u64(7));
+        for _ in 0..2 {
+            let action = MultiAgentSpace::actions(&game)[0];
+            game.step(&action);
+        }
+        let json: serde_json::Value = serde_json::from_str(&game.to_json().unwrap()).unwrap();
+        assert!(Game::from_json(&json.to_string()).is_ok());
+
+        let mut broken = json.clone();
+        broken["turn"] = 7.into();
+        assert!(Game::from_json(&broken.to_string()).is_err());
+
+        // a card of the table back in a hand: one card twice
+        let mut broken = json.clone();
+        let card = broken["table"][0].clone();
+        broken["players"][3].as_array_mut().unwrap().push(card);
+        assert!(Game::from_json(&broken.to_string()).is_err());
+
+        // a card less in a hand
+        let mut broken = json;
+        broken["players"][3].as_array_mut().unwrap().pop();
+        assert!(Game::from_json(&broken.to_string()).is_err());
+    }
+
+    #[test]
+    fn practice_undoes_the_last_human_card() {
+        let mut game = init_game();
+        game.add_human_players(1).unwrap();
+        game.set_practice(true).unwrap();
+        assert!(!game.undo(), "nothing was played yet");
+
+        let before = game.clone();
+        game.remember();
+        game.player_plays(0).unwrap();
+        for _ in 1..4 {
+            game.play_easy();
+        }
+        game.trick().unwrap();
+        assert_eq!(game.tricks.len(), 1);
+
+        assert!(game.undo());
+        assert!(game == before);
+        assert!(!game.undo());
+    }
+
+    #[test]
+    fn only_one_human_can_practice() {
+        let mut game = init_game();
+        game.add_human_players(2).unwrap();
+        assert!(game.set_practice(true).is_err());
+
+        let mut game = init_game();
+        game.set_practice(true).unwrap();
+        assert!(game.add_human_players(2).is_err());
+
+        // without practice mode, remembered cards can't be taken back
+        let mut game = init_game();
+        game.remember();
+        assert!(!game.undo());
+    }
+
+    #[test]
+    fn played_deals_go_on_the_scoreboard() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let mut game = Game::new();
+        game.add_human_players(1).unwrap();
+
+        for dealer in 0..2 {
+            assert!(game.play_deal(&mut Scripted::default(), &None));
+            let tricks = game.round_scores;
+            game.new_deal_with(&mut rng);
+
+            let deal = game.scoreboard().deals().last().unwrap().clone();
+            assert_eq!(deal.tricks, tricks);
+            assert_eq!(deal.tricks.iter().sum::<u32>(), 13);
+            assert_eq!(deal.dealer, dealer);
+        }
+
+        assert_eq!(game.scoreboard().deals().len(), 2);
+        assert_eq!(game.get_ai_score(), game.scoreboard().totals()[0]);
+        assert_eq!(game.round_scores, [0; 4]);
+    }
+
+    #[test]
+    fn deals_played_without_a_ui_are_not_recorded() {
+        let mut rng = ChaCha8Rng::seed_from_u64(13);
+        let mut game = Game::new();
+        game.new_deal_with(&mut rng);
+
+        for _ in 0..3 {
+            game.play_out();
+            game.new_deal_with(&mut rng);
+        }
+
+        assert!(game.scoreboard().deals().is_empty());
+        assert_eq!(game.get_ai_score(), 0);
+    }
+
     #[test]
     fn trick_no_cards_err() {
         let mut game = init_game();
@@ -1065,4 +1928,23 @@ mod tests {
                     == Ordering::Greater));
         }
     }
+
+    #[test]
+    fn deal_is_terminal_after_thirteen_tricks() {
+        let mut game = init_game();
+        game.opponents_play_until_agent(&None);
+
+        for _ in 0..13 {
+            assert!(!game.deal_over());
+            let card = game.alowed_cards()[0];
+            game.agent_plays_trick(card, &None);
+        }
+
+        assert!(game.deal_over());
+
+        <Game as GameSpace<GameState>>::reset(&mut game, &None);
+
+        assert!(!game.deal_over());
+        assert_eq!(game.turn, 0);
+    }
 }
@@ -3,6 +3,7 @@
  */
 
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use crate::{fortify::State, suit::Suit};
 
@@ -95,6 +96,52 @@ impl State for BidState {
     type A = Bid;
 }
 
+/// plain text, without the colours of `Suit`'s display, so it can be written to files
+impl fmt::Display for GameState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+
+        write!(f, "{:>2} cards, ", self.nb_cards)?;
+        match Suit::iterator().nth(self.first_suit as usize) {
+            Some(suit) if self.first_suit >= 0 => write!(
+                f,
+                "{:?} played (follow: {}, higher: {})",
+                suit,
+                yes_no(self.can_follow),
+                yes_no(self.have_higher)
+            )?,
+            _ => write!(f, "to come out")?,
+        }
+
+        let highest: Vec<String> = Suit::iterator()
+            .zip(self.has_highest)
+            .filter(|(_, highest)| *highest)
+            .map(|(suit, _)| format!("{:?}", suit))
+            .collect();
+        write!(
+            f,
+            ", highest: [{}], trump: {}, out of suits: {}",
+            highest.join(", "),
+            yes_no(self.have_trump),
+            self.nb_out_of
+        )
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::PlayWorst(suit) => write!(f, "play worst {:?}", suit),
+            Action::RaiseLow => write!(f, "raise low"),
+            Action::RaiseHigh => write!(f, "raise high"),
+            Action::TrumpHigh => write!(f, "trump high"),
+            Action::TrumpLow => write!(f, "trump low"),
+            Action::PlayBest(suit) => write!(f, "play best {:?}", suit),
+            Action::ComeBest => write!(f, "come best"),
+        }
+    }
+}
+
 impl Bid {
     /// match the Bid to get a value which is used for ordering bids
     fn value(&self) -> i8 {
@@ -125,26 +172,112 @@ impl Bid {
             _ => -1,
         }
     }
+
+    /// the place of the variant in the enum, to separate bids of the same value
+    fn kind(&self) -> u8 {
+        match self {
+            Bid::Pass => 0,
+            Bid::Wait => 1,
+            Bid::Propose(_) => 2,
+            Bid::Accept(..) => 3,
+            Bid::Solo(..) => 4,
+            Bid::SmallMisery => 5,
+            Bid::LargeMisery => 6,
+            Bid::OpenMisery => 7,
+            Bid::Abondance(..) => 8,
+            Bid::SoloSlim(_) => 9,
+            Bid::PassParole => 10,
+            Bid::Trull => 11,
+        }
+    }
 }
 
 impl PartialOrd for Bid {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match self.value().cmp(&other.value()) {
-            // If the bids are equal in score, they are separated in suit!
-            std::cmp::Ordering::Equal => match (self, other) {
-                (Bid::Accept(s1, _), Bid::Accept(s2, _)) => Some(s1.cmp(s2)),
-                (Bid::Solo(s1, _), Bid::Solo(s2, _)) => Some(s1.cmp(s2)),
-                (Bid::Abondance(s1, _), Bid::Abondance(s2, _)) => Some(s1.cmp(s2)),
-                _ => None,
-            },
-            // If bids are not equal in score, just return their ordering
-            other => Some(other),
-        }
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Bid {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap_or(std::cmp::Ordering::Equal)
+        self.value()
+            .cmp(&other.value())
+            // bids of the same kind and score are separated in suit,
+            // other bids of the same score only by their kind, so the order agrees with `Eq`
+            .then_with(|| self.kind().cmp(&other.kind()))
+            .then_with(|| match (self, other) {
+                (Bid::Propose(s1), Bid::Propose(s2)) | (Bid::SoloSlim(s1), Bid::SoloSlim(s2)) => {
+                    s1.cmp(s2)
+                }
+                (Bid::Accept(s1, n1), Bid::Accept(s2, n2))
+                | (Bid::Solo(s1, n1), Bid::Solo(s2, n2))
+                | (Bid::Abondance(s1, n1), Bid::Abondance(s2, n2)) => (s1, n1).cmp(&(s2, n2)),
+                _ => std::cmp::Ordering::Equal,
+            })
+    }
+}
+
+/// The states and tables the tests of the learning share.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::{Action, GameState};
+    use crate::fortify::Q;
+
+    /// a state in which every card can be played
+    pub(crate) fn state(nb_cards: u8) -> GameState {
+        GameState {
+            can_follow: true,
+            have_higher: true,
+            first_suit: -1,
+            has_highest: [false; 4],
+            have_trump: true,
+            nb_cards,
+            nb_out_of: 0,
+        }
+    }
+
+    /// one state with two values, 1 and 3
+    pub(crate) fn table() -> Q<GameState> {
+        let mut q = Q::new();
+        q.insert(
+            state(0),
+            [(Action::RaiseLow, 1.0), (Action::RaiseHigh, 3.0)]
+                .into_iter()
+                .collect(),
+        );
+        q
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bids_are_only_equal_to_themselves() {
+        let bids = [
+            Bid::Pass,
+            Bid::Wait,
+            Bid::Propose(Suit::Hearts),
+            Bid::Accept(Suit::Hearts, 8),
+            Bid::Accept(Suit::Spades, 8),
+            Bid::Solo(Suit::Hearts, 5),
+            Bid::SmallMisery,
+            Bid::Abondance(Suit::Clubs, 9),
+            Bid::Trull,
+            Bid::PassParole,
+        ];
+
+        for a in &bids {
+            for b in &bids {
+                assert_eq!(
+                    a.cmp(b) == std::cmp::Ordering::Equal,
+                    a == b,
+                    "{a:?} and {b:?}"
+                );
+            }
+        }
+        assert!(Bid::Accept(Suit::Hearts, 8) < Bid::Solo(Suit::Hearts, 5));
+        assert!(Bid::Trull < Bid::LargeMisery);
     }
 }
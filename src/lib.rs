@@ -6,8 +6,17 @@ This AI currently isn't really implemented yet, but the general structures of th
 pub mod deck;
 pub mod suit;
 pub mod card;
+pub mod cardset;
+pub mod notation;
+pub mod render;
+pub mod search;
+pub mod review;
+pub mod scoreboard;
+pub mod session;
 pub mod game;
 pub mod player;
 pub mod show;
+pub mod tui;
+pub mod ui;
 pub mod fortify;
 pub mod gamestate;
\ No newline at end of file
@@ -0,0 +1,285 @@
+/*!
+ * Text notation of cards, suits and hands, to write them down and read them back.
+ *
+ * A card is a rank followed by a suit: `AH`, `10s`, `T♠`, `7♦`.
+ * Ranks are `A K Q J T` or `10`, and `2` to `9`.
+ * Suits are `S C D H`, the symbols `♠ ♣ ♦ ♥`, or their names.
+ * Everything is case insensitive.
+ *
+ * A hand is a list of cards, `AH 10S 7♦`, or a group of ranks per suit,
+ * where `-` is a suit without cards: `S:AKQ2 H:T98 D:- C:J765432`.
+ *
+ * The colours of `Display` are ignored, so a printed card can be read back.
+ */
+
+use crate::{card::Card, cardset::CardSet, deck::Deck, suit::Suit};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Why a card, suit or hand could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// there was nothing to read
+    Empty,
+    /// not a rank: `A K Q J T 10 9 .. 2`
+    Rank(String),
+    /// not a suit: `S C D H`, `♠ ♣ ♦ ♥` or the name of a suit
+    Suit(String),
+    /// a suit group of a hand, which isn't like `S:AKQ2` or `D:-`
+    Group(String),
+    /// the same card is in a hand twice
+    Duplicate(Card),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "nothing to read"),
+            ParseError::Rank(rank) => write!(f, "'{rank}' is not a rank (A K Q J T 9 .. 2)"),
+            ParseError::Suit(suit) => write!(f, "'{suit}' is not a suit (S C D H)"),
+            ParseError::Group(group) => write!(f, "'{group}' is not a suit with ranks, like S:AK2"),
+            ParseError::Duplicate(card) => write!(f, "{} is in the hand twice", card.notation()),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// the text without the colours of `Display`
+fn strip_colours(s: &str) -> String {
+    let mut stripped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip the escape sequence, up to and including its final letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+/// the number of a rank, as in `Card::number`
+fn parse_rank(rank: &str) -> Result<u8, ParseError> {
+    match rank.to_ascii_uppercase().as_str() {
+        "A" => Ok(1),
+        "K" => Ok(13),
+        "Q" => Ok(12),
+        "J" => Ok(11),
+        "T" | "10" => Ok(10),
+        number => match number.parse() {
+            Ok(number @ 2..=9) => Ok(number),
+            _ => Err(ParseError::Rank(rank.to_string())),
+        },
+    }
+}
+
+/// the ranks of one suit group, e.g. `AKQ2` or `T987`, in the order they are written
+fn parse_ranks(ranks: &str) -> Result<Vec<u8>, ParseError> {
+    let mut numbers = Vec::new();
+    let mut rest = ranks;
+
+    while !rest.is_empty() {
+        let len = match rest.starts_with("10") {
+            true => 2,
+            false => rest.chars().next().map_or(1, char::len_utf8),
+        };
+        numbers.push(parse_rank(&rest[..len])?);
+        rest = &rest[len..];
+    }
+
+    Ok(numbers)
+}
+
+impl FromStr for Suit {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let suit = strip_colours(s);
+
+        match suit.trim().to_lowercase().as_str() {
+            "s" | "♠" | "♤" | "spades" => Ok(Suit::Spades),
+            "c" | "♣" | "♧" | "clubs" => Ok(Suit::Clubs),
+            "d" | "♦" | "♢" | "diamonds" => Ok(Suit::Diamonds),
+            "h" | "♥" | "♡" | "hearts" => Ok(Suit::Hearts),
+            "" => Err(ParseError::Empty),
+            _ => Err(ParseError::Suit(s.trim().to_string())),
+        }
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let card = strip_colours(s);
+        let card = card.trim();
+
+        // the suit is the last character, the rank is everything before it
+        let (split, _) = card.char_indices().last().ok_or(ParseError::Empty)?;
+        if split == 0 {
+            return Err(ParseError::Suit(card.to_string()));
+        }
+
+        Ok(Card {
+            number: parse_rank(&card[..split])?,
+            suit: card[split..].parse()?,
+        })
+    }
+}
+
+/// A hand, see the module documentation. The cards are sorted.
+impl FromStr for Deck {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hand = strip_colours(s);
+        let mut cards = CardSet::EMPTY;
+
+        let mut add = |card: Card| match cards.insert(&card) {
+            true => Ok(()),
+            false => Err(ParseError::Duplicate(card)),
+        };
+
+        for token in hand.split_whitespace() {
+            match token.split_once(':') {
+                Some((suit, ranks)) => {
+                    let suit: Suit = suit
+                        .parse()
+                        .map_err(|_| ParseError::Group(token.to_string()))?;
+                    if ranks == "-" {
+                        continue;
+                    }
+                    if ranks.is_empty() {
+                        return Err(ParseError::Group(token.to_string()));
+                    }
+                    for number in parse_ranks(ranks)? {
+                        add(Card { suit, number })?;
+                    }
+                }
+                None => add(token.parse()?)?,
+            }
+        }
+
+        Ok(cards.to_deck())
+    }
+}
+
+impl Suit {
+    /// the letter of this suit: S, C, D or H
+    pub fn letter(&self) -> char {
+        match self {
+            Suit::Spades => 'S',
+            Suit::Clubs => 'C',
+            Suit::Diamonds => 'D',
+            Suit::Hearts => 'H',
+        }
+    }
+}
+
+impl Card {
+    /// the letter or digit of the number of this card, `T` for a ten
+    pub fn rank(&self) -> char {
+        match self.number {
+            1 => 'A',
+            10 => 'T',
+            11 => 'J',
+            12 => 'Q',
+            13 => 'K',
+            number => (b'0' + number) as char,
+        }
+    }
+
+    /// the card in plain text, like `AH` or `TS`
+    pub fn notation(&self) -> String {
+        format!("{}{}", self.rank(), self.suit.letter())
+    }
+}
+
+impl Deck {
+    /// the hand in plain text, like `S:AKQ2 C:J765432 D:- H:T98`
+    pub fn notation(&self) -> String {
+        let set = self.set();
+
+        Suit::iterator()
+            .map(|suit| {
+                let ranks: String = match set.of_suit(*suit) {
+                    cards if cards.is_empty() => "-".to_string(),
+                    cards => cards.iter().rev().map(|card| card.rank()).collect(),
+                };
+                format!("{}:{ranks}", suit.letter())
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(suit: Suit, number: u8) -> Card {
+        Card { suit, number }
+    }
+
+    #[test]
+    fn cards_in_any_notation() {
+        assert_eq!("AH".parse(), Ok(card(Suit::Hearts, 1)));
+        assert_eq!("10s".parse(), Ok(card(Suit::Spades, 10)));
+        assert_eq!("T♠".parse(), Ok(card(Suit::Spades, 10)));
+        assert_eq!(" 7♦ ".parse(), Ok(card(Suit::Diamonds, 7)));
+        assert_eq!("qc".parse(), Ok(card(Suit::Clubs, 12)));
+
+        assert_eq!("1H".parse::<Card>(), Err(ParseError::Rank("1".into())));
+        assert_eq!("AX".parse::<Card>(), Err(ParseError::Suit("X".into())));
+        assert_eq!("H".parse::<Card>(), Err(ParseError::Suit("H".into())));
+        assert_eq!("".parse::<Card>(), Err(ParseError::Empty));
+    }
+
+    #[test]
+    fn printed_cards_read_back() {
+        for card in Deck::new_full() {
+            assert_eq!(card.to_string().parse(), Ok(card.clone()));
+            assert_eq!(card.notation().parse(), Ok(card.clone()));
+            assert_eq!(card.suit.to_string().parse(), Ok(card.suit));
+        }
+    }
+
+    #[test]
+    fn hands_by_suit() {
+        let hand: Deck = "S:AKQ2 H:T98 D:- C:J765432".parse().unwrap();
+
+        assert_eq!(hand.size(), 14);
+        assert_eq!(hand.get_suit_amount(&Suit::Clubs), 7);
+        assert!(!hand.has_suit(&Suit::Diamonds));
+        assert!(hand.contains(&card(Suit::Hearts, 10)));
+        assert_eq!(hand.notation(), "S:AKQ2 C:J765432 D:- H:T98");
+        assert_eq!(hand.notation().parse::<Deck>().unwrap().cards, hand.cards);
+
+        let listed: Deck = "AS KS QS 2S 10H 9H 8H JC 7C 6C 5C 4C 3C 2C"
+            .parse()
+            .unwrap();
+        assert_eq!(listed.cards, hand.cards);
+    }
+
+    #[test]
+    fn bad_hands() {
+        let error = |hand: &str| hand.parse::<Deck>().err();
+
+        assert_eq!(
+            error("S:AKA"),
+            Some(ParseError::Duplicate(card(Suit::Spades, 1)))
+        );
+        assert_eq!(error("X:AK"), Some(ParseError::Group("X:AK".into())));
+        assert_eq!(error("S:"), Some(ParseError::Group("S:".into())));
+        assert_eq!(error("H:A1"), Some(ParseError::Rank("1".into())));
+    }
+}
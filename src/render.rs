@@ -0,0 +1,308 @@
+/*!
+ * How cards, hands and tables are drawn: with ANSI colours, as plain Unicode, or as pure ASCII.
+ *
+ * The `Display` impls of `Card`, `Suit` and `Deck`, and everything in `show`, use the current
+ * style. It is detected once: `WHISTER_STYLE` (ansi, unicode or ascii) when it is set,
+ * plain Unicode when stdout is not a terminal or `NO_COLOR` is set,
+ * ASCII on a dumb terminal, and ANSI colours otherwise.
+ */
+
+use std::fmt;
+use std::io::{stdout, IsTerminal};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::{card::Card, deck::Deck, suit::Suit};
+
+/// the environment variable which picks the style
+pub const STYLE_VAR: &str = "WHISTER_STYLE";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Style {
+    /// coloured cards, bold and dimmed text: `K♥` on a white background
+    Ansi,
+    /// no escape codes, but suit symbols and box drawing: `K♥`
+    Unicode,
+    /// only ASCII characters: `KH`
+    Ascii,
+}
+
+/// the characters of a box around some text
+pub struct Frame {
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+    pub horizontal: char,
+    pub vertical: char,
+    /// where a line stops, left of some text
+    pub left_end: char,
+    /// where a line starts again, right of some text
+    pub right_end: char,
+}
+
+const UNICODE_FRAME: Frame = Frame {
+    top_left: '╭',
+    top_right: '╮',
+    bottom_left: '╰',
+    bottom_right: '╯',
+    horizontal: '─',
+    vertical: '│',
+    left_end: '╴',
+    right_end: '╶',
+};
+
+const ASCII_FRAME: Frame = Frame {
+    top_left: '+',
+    top_right: '+',
+    bottom_left: '+',
+    bottom_right: '+',
+    horizontal: '-',
+    vertical: '|',
+    left_end: '-',
+    right_end: '-',
+};
+
+impl Style {
+    pub const ALL: [Style; 3] = [Style::Ansi, Style::Unicode, Style::Ascii];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Style::Ansi => "ansi",
+            Style::Unicode => "unicode",
+            Style::Ascii => "ascii",
+        }
+    }
+
+    /// the style for this environment, see the module documentation
+    pub fn detect() -> Style {
+        if let Some(style) = std::env::var(STYLE_VAR).ok().and_then(|s| s.parse().ok()) {
+            return style;
+        }
+
+        if !stdout().is_terminal() || std::env::var_os("NO_COLOR").is_some() {
+            Style::Unicode
+        } else if std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+            Style::Ascii
+        } else {
+            Style::Ansi
+        }
+    }
+
+    pub fn frame(&self) -> &'static Frame {
+        match self {
+            Style::Ascii => &ASCII_FRAME,
+            _ => &UNICODE_FRAME,
+        }
+    }
+
+    /// the escape `code` in ANSI style, nothing otherwise
+    pub fn ansi(&self, code: &'static str) -> &'static str {
+        match self {
+            Style::Ansi => code,
+            _ => "",
+        }
+    }
+
+    /// `text` between an escape code and a reset, in ANSI style
+    fn paint(&self, code: &'static str, text: impl fmt::Display) -> String {
+        match self {
+            Style::Ansi => format!("{code}{text}{RESET}"),
+            _ => text.to_string(),
+        }
+    }
+
+    pub fn suit(&self, suit: Suit) -> String {
+        let symbol = match self {
+            Style::Ascii => return suit.letter().to_string(),
+            _ => match suit {
+                Suit::Spades => '♠',
+                Suit::Clubs => '♣',
+                Suit::Diamonds => '♦',
+                Suit::Hearts => '♥',
+            },
+        };
+
+        match suit {
+            Suit::Spades | Suit::Clubs => self.paint("\x1b[30;47m", symbol),
+            Suit::Diamonds | Suit::Hearts => self.paint("\x1b[91;47m", symbol),
+        }
+    }
+
+    pub fn card(&self, card: &Card) -> String {
+        format!(
+            "{}{}{}",
+            self.ansi("\x1b[47;30m"),
+            card.rank(),
+            self.suit(card.suit)
+        )
+    }
+
+    /// a hand, one line per suit, with the selected card highlighted
+    pub fn hand(&self, hand: &Deck) -> String {
+        // do not show an empty deck!
+        if hand.is_empty() {
+            return "  Empty".to_string();
+        }
+
+        let frame = self.frame();
+        let mut text = self.dim(format!("{}{}", frame.top_left, frame.left_end));
+
+        let mut current_suit = hand.cards[0].suit;
+
+        for (id, card) in hand.iter().enumerate() {
+            if card.suit != current_suit {
+                text.push_str(&format!("\n{} ", self.dim(frame.vertical)));
+                current_suit = card.suit;
+            }
+
+            // show card, selected card shown differently
+            let card = format!("{}, ", self.card(card));
+            match id == hand.selected_id() {
+                true => text.push_str(&self.selected(card)),
+                false => text.push_str(&card),
+            }
+        }
+
+        let count = format!(
+            "{}{}count: {}",
+            frame.bottom_left,
+            frame.left_end,
+            hand.size()
+        );
+        text.push_str(&format!("\n{}", self.dim(count)));
+
+        text
+    }
+
+    /// a line of `width` horizontal frame characters
+    pub fn line(&self, width: usize) -> String {
+        self.frame().horizontal.to_string().repeat(width)
+    }
+
+    pub fn bold(&self, text: impl fmt::Display) -> String {
+        self.paint(BOLD, text)
+    }
+
+    pub fn dim(&self, text: impl fmt::Display) -> String {
+        self.paint("\x1b[2m", text)
+    }
+
+    pub fn italic(&self, text: impl fmt::Display) -> String {
+        self.paint("\x1b[3m", text)
+    }
+
+    pub fn green(&self, text: impl fmt::Display) -> String {
+        self.paint("\x1b[92m", text)
+    }
+
+    /// the highlighted item of a list: reversed colours, or between brackets
+    pub fn selected(&self, text: impl fmt::Display) -> String {
+        match self {
+            Style::Ansi => self.paint("\x1b[7m", text),
+            _ => format!("[{text}]"),
+        }
+    }
+}
+
+/// the ANSI code which resets all colours and attributes
+pub const RESET: &str = "\x1b[0m";
+
+/// the ANSI code for bold text, in which answers to prompts are typed
+pub const BOLD: &str = "\x1b[1m";
+
+/// the style in use, 0 when it isn't detected yet
+static STYLE: AtomicU8 = AtomicU8::new(0);
+
+/// the style everything is drawn in, see the module documentation
+pub fn style() -> Style {
+    match STYLE.load(Ordering::Relaxed) {
+        1 => Style::Ansi,
+        2 => Style::Unicode,
+        3 => Style::Ascii,
+        _ => {
+            let style = Style::detect();
+            set_style(style);
+            style
+        }
+    }
+}
+
+/// draw everything in this style from now on, instead of the detected one
+pub fn set_style(style: Style) {
+    let value = match style {
+        Style::Ansi => 1,
+        Style::Unicode => 2,
+        Style::Ascii => 3,
+    };
+    STYLE.store(value, Ordering::Relaxed);
+}
+
+/// the escape `code` when drawing with ANSI colours, see `Style::ansi`
+pub fn ansi(code: &'static str) -> &'static str {
+    style().ansi(code)
+}
+
+pub fn bold(text: impl fmt::Display) -> String {
+    style().bold(text)
+}
+
+pub fn italic(text: impl fmt::Display) -> String {
+    style().italic(text)
+}
+
+/// is stdout a terminal, which can be cleared and has a cursor to move?
+pub fn is_terminal() -> bool {
+    stdout().is_terminal()
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Style::ALL
+            .into_iter()
+            .find(|style| style.name() == s.to_lowercase())
+            .ok_or_else(|| format!("{s} is not ansi, unicode or ascii"))
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cards_in_every_style() {
+        let ten = Card {
+            suit: Suit::Hearts,
+            number: 10,
+        };
+
+        assert_eq!(Style::Ascii.card(&ten), "TH");
+        assert_eq!(Style::Unicode.card(&ten), "T♥");
+        assert_eq!(Style::Ansi.card(&ten), "\x1b[47;30mT\x1b[91;47m♥\x1b[0m");
+        assert_eq!("ASCII".parse(), Ok(Style::Ascii));
+    }
+
+    #[test]
+    fn plain_styles_have_no_escape_codes() {
+        let mut hand: Deck = "S:AK2 H:T9 D:-".parse().unwrap();
+        hand.set_selected(0);
+
+        for style in [Style::Unicode, Style::Ascii] {
+            let text = style.hand(&hand);
+            assert!(!text.contains('\x1b'), "{style} has escape codes");
+            assert_eq!(text.lines().count(), 3);
+        }
+
+        let ascii = Style::Ascii.hand(&hand);
+        assert!(ascii.is_ascii());
+        assert!(ascii.contains("[2S, ]"), "the first card is selected");
+    }
+}
@@ -0,0 +1,151 @@
+/*!
+ * Looking back at a deal: every card a human played, next to the card the AI would have played.
+ *
+ * The AI is the model when it knows the situation, and the rule based AI otherwise.
+ * Both cards are played out with `search` on the same deals, the difference in tricks
+ * is what the card cost.
+ */
+
+use rand::Rng;
+
+use crate::card::Card;
+use crate::deck::CardID;
+use crate::fortify::{self, MultiAgentSpace, Q};
+use crate::game::Game;
+use crate::gamestate::GameState;
+use crate::search;
+
+/// amount of playouts per card, when reviewing a deal
+pub const REVIEW_ROLLOUTS: usize = 100;
+
+/// a card which costs less tricks than this is not counted as a mistake
+pub const MISTAKE_MARGIN: f64 = 0.25;
+
+/// who advised the card
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Advisor {
+    Model,
+    RuleBased,
+}
+
+/// one card of a human player, and what the AI would have done instead
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decision {
+    pub player: usize,
+    /// the trick the card was played in, starting from 1
+    pub trick: usize,
+    pub played: Card,
+    pub advised: Card,
+    pub advisor: Advisor,
+    /// the tricks the player could expect after the played card
+    pub played_tricks: f64,
+    /// the tricks the player could expect after the advised card
+    pub advised_tricks: f64,
+}
+
+impl Decision {
+    /// the tricks the played card cost, compared to the advised one
+    pub fn lost(&self) -> f64 {
+        self.advised_tricks - self.played_tricks
+    }
+
+    pub fn is_mistake(&self) -> bool {
+        self.played != self.advised && self.lost() >= MISTAKE_MARGIN
+    }
+}
+
+/// the card the strongest AI at hand would play: the model, if it knows this situation
+pub fn advice(game: &Game, q: Option<&Q<GameState>>) -> (CardID, Advisor) {
+    let state = game.state_of(game.to_act());
+    match q.map(|q| fortify::best_action_score(q, &state)) {
+        Some(Ok((action, _))) => (game.action_card_id(&action), Advisor::Model),
+        _ => (game.easy_card_id(), Advisor::RuleBased),
+    }
+}
+
+/// every card the humans played in this deal, see `Game::human_decisions`
+pub fn review(game: &Game, q: Option<&Q<GameState>>, rollouts: usize) -> Vec<Decision> {
+    review_with(game, q, rollouts, &mut rand::thread_rng())
+}
+
+/// like `review`, with the given source of randomness
+pub fn review_with<R: Rng + ?Sized>(
+    game: &Game,
+    q: Option<&Q<GameState>>,
+    rollouts: usize,
+    rng: &mut R,
+) -> Vec<Decision> {
+    game.human_decisions()
+        .into_iter()
+        .map(|(before, played)| {
+            let player = before.to_act();
+            let hand = &before.players[player];
+            let played_id = before
+                .card_id_of(player, &played)
+                .expect("the played card was in the hand");
+            let (advised_id, advisor) = advice(before, q);
+
+            let (played_tricks, advised_tricks) = match advised_id == played_id {
+                true => {
+                    let tricks = search::estimate_card_with(before, played_id, rollouts, rng);
+                    (tricks, tricks)
+                }
+                false => {
+                    let tricks =
+                        search::estimate_each_with(before, &[played_id, advised_id], rollouts, rng);
+                    (tricks[0], tricks[1])
+                }
+            };
+
+            Decision {
+                player,
+                trick: before.tricks_played() + 1,
+                played,
+                advised: hand.card(advised_id).clone(),
+                advisor,
+                played_tricks,
+                advised_tricks,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn every_human_card_is_reviewed() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let mut game = Game::new();
+        game.new_deal_with(&mut rng);
+        game.add_human_players(1).unwrap();
+
+        let mut played = Vec::new();
+        while !game.deal_over() {
+            let action = MultiAgentSpace::actions(&game)[0];
+            if game.to_act() == 0 {
+                let card = game.action_card_id(&action);
+                played.push(game.players[0].card(card).clone());
+                game.remember();
+            }
+            game.step(&action);
+        }
+
+        let decisions = review_with(&game, None, 5, &mut rng);
+
+        assert_eq!(decisions.len(), 13);
+        for (number, (decision, card)) in decisions.iter().zip(played).enumerate() {
+            assert_eq!(decision.player, 0);
+            assert_eq!(decision.trick, number + 1);
+            assert_eq!(decision.played, card);
+            assert_eq!(decision.advisor, Advisor::RuleBased);
+            if decision.played == decision.advised {
+                assert_eq!(decision.lost(), 0.0);
+                assert!(!decision.is_mistake());
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+/*!
+ * The scores of a game, deal by deal: who dealt, what was played for, and the tricks everyone made.
+ *
+ * There is no bidding yet, so there are no contracts or declarers, and every trick is worth a point.
+ */
+
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::fortify::data::DataError;
+use crate::render;
+
+/// the outcome of one deal
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DealRecord {
+    pub dealer: usize,
+    /// what was bid for, `None` when nothing was
+    pub contract: Option<String>,
+    /// the players who played for the contract
+    pub declarers: Vec<usize>,
+    /// the tricks every player made
+    pub tricks: [u32; 4],
+    /// the points every player got for this deal
+    pub points: [u32; 4],
+}
+
+/// every deal of a game, see `Game::scoreboard`
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Scoreboard {
+    deals: Vec<DealRecord>,
+}
+
+/// the short name of a player, in a column
+fn name(player: usize) -> String {
+    match player {
+        0 => "You".to_string(),
+        _ => format!("P{player}"),
+    }
+}
+
+impl Scoreboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, deal: DealRecord) {
+        self.deals.push(deal);
+    }
+
+    /// the deals, the first one first
+    pub fn deals(&self) -> &[DealRecord] {
+        &self.deals
+    }
+
+    pub fn clear(&mut self) {
+        self.deals.clear();
+    }
+
+    /// the points of every player, over all deals
+    pub fn totals(&self) -> [u32; 4] {
+        let mut totals = [0; 4];
+        for deal in &self.deals {
+            for (total, points) in totals.iter_mut().zip(deal.points) {
+                *total += points;
+            }
+        }
+        totals
+    }
+
+    /// The scoreboard as a table, one row per deal and the totals below.
+    pub fn lines(&self) -> Vec<String> {
+        let style = render::style();
+        let columns = |values: [u32; 4]| -> String {
+            values.iter().map(|value| format!("{value:>4}")).collect()
+        };
+        let names: String = (0..4)
+            .map(|player| format!("{:>4}", name(player)))
+            .collect();
+
+        let mut lines = vec![
+            style.bold(format!("{:<34}{:^16}  {:^16}", "", "Tricks", "Points")),
+            style.bold(format!(
+                "{:>4}  {:<8}  {:<8}  {:<8}{names}  {names}",
+                "Deal", "Dealer", "Contract", "Declarer"
+            )),
+        ];
+
+        for (number, deal) in self.deals.iter().enumerate() {
+            let declarers = match deal.declarers.is_empty() {
+                true => "-".to_string(),
+                false => deal
+                    .declarers
+                    .iter()
+                    .map(|d| name(*d))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            };
+            lines.push(format!(
+                "{:>4}  {:<8}  {:<8}  {:<8}{}  {}",
+                number + 1,
+                name(deal.dealer),
+                deal.contract.as_deref().unwrap_or("-"),
+                declarers,
+                columns(deal.tricks),
+                columns(deal.points)
+            ));
+        }
+
+        if self.deals.is_empty() {
+            lines.push(style.dim("No deals were played yet."));
+        }
+
+        lines.push(style.bold(format!(
+            "{:<34}{:16}  {}",
+            "Total",
+            "",
+            columns(self.totals())
+        )));
+        lines
+    }
+
+    /// Write one row per deal, with a column for the tricks and the points of every player.
+    pub fn write_csv<W: io::Write>(&self, writer: W) -> Result<(), DataError> {
+        let mut wtr = csv::Writer::from_writer(writer);
+
+        let mut header = vec![
+            "deal".to_string(),
+            "dealer".to_string(),
+            "contract".to_string(),
+            "declarers".to_string(),
+        ];
+        header.extend((0..4).map(|player| format!("tricks_{player}")));
+        header.extend((0..4).map(|player| format!("points_{player}")));
+        wtr.write_record(&header)?;
+
+        for (number, deal) in self.deals.iter().enumerate() {
+            let mut record = vec![
+                (number + 1).to_string(),
+                deal.dealer.to_string(),
+                deal.contract.clone().unwrap_or_default(),
+                deal.declarers
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ];
+            record.extend(deal.tricks.iter().map(|tricks| tricks.to_string()));
+            record.extend(deal.points.iter().map(|points| points.to_string()));
+            wtr.write_record(&record)?;
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// export the scoreboard to a csv file at `path`, see `write_csv`
+    pub fn save_csv(&self, path: &Path) -> Result<(), DataError> {
+        self.write_csv(std::fs::File::create(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deal(dealer: usize, tricks: [u32; 4]) -> DealRecord {
+        DealRecord {
+            dealer,
+            contract: None,
+            declarers: vec![],
+            tricks,
+            points: tricks,
+        }
+    }
+
+    #[test]
+    fn totals_add_up_the_deals() {
+        let mut board = Scoreboard::new();
+        assert_eq!(board.totals(), [0; 4]);
+
+        board.record(deal(0, [4, 3, 3, 3]));
+        board.record(deal(1, [1, 5, 2, 5]));
+
+        assert_eq!(board.totals(), [5, 8, 5, 8]);
+        // two header lines, a line per deal and the totals
+        assert_eq!(board.lines().len(), 5);
+    }
+
+    #[test]
+    fn deals_are_exported_as_csv() {
+        let mut board = Scoreboard::new();
+        board.record(deal(0, [4, 3, 3, 3]));
+        board.record(DealRecord {
+            contract: Some("solo".to_string()),
+            declarers: vec![1, 3],
+            ..deal(1, [1, 5, 2, 5])
+        });
+
+        let mut csv = Vec::new();
+        board.write_csv(&mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "deal,dealer,contract,declarers,tricks_0,tricks_1,tricks_2,tricks_3,\
+             points_0,points_1,points_2,points_3\n\
+             1,0,,,4,3,3,3,4,3,3,3\n\
+             2,1,solo,1 3,1,5,2,5,1,5,2,5\n"
+        );
+    }
+}
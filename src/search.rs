@@ -0,0 +1,142 @@
+/*!
+ * Monte Carlo search: what a card is worth, found by playing the rest of the deal many times.
+ *
+ * The player to move doesn't know the hands of the others, so for every playout
+ * the cards they can't see are dealt again (see `Game::redeal_hidden`).
+ * After the card, every player plays like the rule based AI.
+ * Cards that are compared are played out on the same deals, so that only the card differs.
+ */
+
+use rand::Rng;
+
+use crate::deck::CardID;
+use crate::fortify::MultiAgentSpace;
+use crate::game::Game;
+
+/// amount of playouts per card, when asking for a hint
+pub const HINT_ROLLOUTS: usize = 200;
+
+/// how many tricks a card is expected to win, in the rest of the deal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub card: CardID,
+    pub tricks: f64,
+}
+
+/// the estimates of every card the player to move can play, the best one first
+pub fn estimate_cards(game: &Game, rollouts: usize) -> Vec<Estimate> {
+    estimate_cards_with(game, rollouts, &mut rand::thread_rng())
+}
+
+/// like `estimate_cards`, with the given source of randomness
+pub fn estimate_cards_with<R: Rng + ?Sized>(
+    game: &Game,
+    rollouts: usize,
+    rng: &mut R,
+) -> Vec<Estimate> {
+    let cards = game.alowed_cards();
+    let mut estimates: Vec<Estimate> = estimate_each_with(game, &cards, rollouts, rng)
+        .into_iter()
+        .zip(cards)
+        .map(|(tricks, card)| Estimate { card, tricks })
+        .collect();
+
+    estimates.sort_by(|a, b| b.tricks.total_cmp(&a.tricks));
+    estimates
+}
+
+/// the tricks the player to move can expect after playing `card`
+pub fn estimate_card_with<R: Rng + ?Sized>(
+    game: &Game,
+    card: CardID,
+    rollouts: usize,
+    rng: &mut R,
+) -> f64 {
+    estimate_each_with(game, &[card], rollouts, rng)[0]
+}
+
+/// the tricks the player to move can expect after each of `cards`,
+/// which are all played out on the same redeals
+pub fn estimate_each_with<R: Rng + ?Sized>(
+    game: &Game,
+    cards: &[CardID],
+    rollouts: usize,
+    rng: &mut R,
+) -> Vec<f64> {
+    let player = game.to_act();
+    let won = game.tricks_of(player);
+
+    let mut tricks = vec![0u32; cards.len()];
+    for _ in 0..rollouts {
+        let redealt = game.redeal_hidden(player, rng);
+        for (card, tricks) in cards.iter().zip(&mut tricks) {
+            let mut playout = redealt.clone();
+            playout
+                .player_plays(*card)
+                .expect("the card is alowed to be played");
+            playout.play_out();
+            *tricks += playout.tricks_of(player) - won;
+        }
+    }
+
+    tricks
+        .into_iter()
+        .map(|tricks| tricks as f64 / rollouts.max(1) as f64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn hidden_cards_are_dealt_again() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let mut game = Game::new();
+        game.new_deal_with(&mut rng);
+        for _ in 0..5 {
+            let action = MultiAgentSpace::actions(&game)[0];
+            game.step(&action);
+        }
+
+        let player = game.to_act();
+        let redealt = game.redeal_hidden(player, &mut rng);
+
+        assert!(redealt.players[player] == game.players[player]);
+        for other in 0..4 {
+            assert_eq!(redealt.players[other].size(), game.players[other].size());
+        }
+        assert!(
+            redealt.players != game.players,
+            "the other hands are the same"
+        );
+    }
+
+    #[test]
+    fn every_playable_card_is_estimated() {
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let mut game = Game::new();
+        game.new_deal_with(&mut rng);
+
+        let estimates = estimate_cards_with(&game, 20, &mut rng);
+
+        assert_eq!(estimates.len(), game.alowed_cards().len());
+        assert!(estimates.windows(2).all(|e| e[0].tricks >= e[1].tricks));
+        assert!(estimates.iter().all(|e| (0.0..=13.0).contains(&e.tricks)));
+    }
+
+    #[test]
+    fn compared_cards_are_played_on_the_same_deals() {
+        let mut rng = ChaCha8Rng::seed_from_u64(9);
+        let mut game = Game::new();
+        game.new_deal_with(&mut rng);
+
+        // the same card twice: only the deals could make a difference
+        let card = game.alowed_cards()[0];
+        let tricks = estimate_each_with(&game, &[card, card], 20, &mut rng);
+
+        assert_eq!(tricks[0], tricks[1]);
+    }
+}
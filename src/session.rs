@@ -0,0 +1,137 @@
+/*!
+ * A game which was quit halfway, saved in the data directory to be resumed on the next launch.
+ */
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::fortify::{
+    data::{self, DataError},
+    model,
+};
+use crate::game::Game;
+
+/// the name of the session file in the data directory
+pub const SESSION_FILE: &str = "session.json";
+
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    /// the scores, the dealer and the deal as far as it was played
+    pub game: Game,
+    /// the model the opponents played with, the rule based opponent when there is none
+    pub model: Option<String>,
+    /// the amount of deals that were played before this one
+    pub deals: usize,
+    /// when the session was saved, in seconds since the unix epoch
+    pub saved: u64,
+}
+
+impl Session {
+    pub fn new(game: Game, model: Option<String>, deals: usize) -> Self {
+        Session {
+            game,
+            model,
+            deals,
+            saved: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        }
+    }
+
+    pub fn path() -> Result<PathBuf, DataError> {
+        Ok(data::get_data_dir()?.join(SESSION_FILE))
+    }
+
+    /// save the session, replacing the one that was saved before
+    pub fn save(&self) -> Result<(), DataError> {
+        self.save_to(&Self::path()?)
+    }
+
+    /// the saved session, if there is one
+    pub fn load() -> Result<Option<Session>, DataError> {
+        Self::load_from(&Self::path()?)
+    }
+
+    /// forget the saved session, e.g. once it is resumed or finished
+    pub fn remove() -> Result<(), DataError> {
+        Self::remove_at(&Self::path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), DataError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Option<Session>, DataError> {
+        match fs::read_to_string(path) {
+            Ok(json) => {
+                let session: Session = serde_json::from_str(&json)?;
+                session.game.validate().map_err(DataError::Invalid)?;
+                Ok(Some(session))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn remove_at(path: &Path) -> Result<(), DataError> {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// the day it was saved, as `yyyy-mm-dd`
+    pub fn date(&self) -> String {
+        model::date(self.saved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_are_saved_and_removed() {
+        let path =
+            std::env::temp_dir().join(format!("whister-session-{}.json", std::process::id()));
+
+        assert!(Session::load_from(&path).unwrap().is_none());
+
+        let session = Session::new(Game::new(), Some("hard".to_string()), 3);
+        session.save_to(&path).unwrap();
+
+        let resumed = Session::load_from(&path)
+            .unwrap()
+            .expect("the session was saved");
+        assert!(resumed.game == session.game);
+        assert_eq!(resumed.model.as_deref(), Some("hard"));
+        assert_eq!(resumed.deals, 3);
+
+        Session::remove_at(&path).unwrap();
+        assert!(Session::load_from(&path).unwrap().is_none());
+    }
+
+    #[test]
+    fn broken_sessions_are_rejected() {
+        let path = std::env::temp_dir().join(format!(
+            "whister-broken-session-{}.json",
+            std::process::id()
+        ));
+
+        let session = Session::new(Game::new(), None, 0);
+        let mut json: serde_json::Value = serde_json::to_value(&session).unwrap();
+        json["game"]["turn"] = 4.into();
+        fs::write(&path, json.to_string()).unwrap();
+        assert!(matches!(
+            Session::load_from(&path),
+            Err(DataError::Invalid(_))
+        ));
+
+        Session::remove_at(&path).unwrap();
+    }
+}
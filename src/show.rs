@@ -10,66 +10,60 @@ use std::{
 
 use termion::{event::Key, input::TermRead, raw::IntoRawMode};
 
-use crate::deck::Deck;
-
-macro_rules! print_player {
-    ($x:expr) => {
-        print!(
-            "{}",
-            match $x {
-                0 => "\x1b[1;92mYou!".to_string(),
-                _ => format!("\x1b[1;91mPlayer {}", $x),
-            }
-        );
-        clear_fmt();
-    };
-}
+use crate::{
+    render,
+    review::{Advisor, Decision},
+};
 
+/// clear the terminal, when there is one
 pub fn clear() {
-    println!("\x1b[1J\x1b[H");
-}
-
-fn clear_fmt() {
-    print!("\x1b[0m");
-}
-
-pub fn show_table_wait(table: &Deck, player: usize, first: usize) {
-    clear();
-    table.show_as_table(player, first, 5);
-    thread::sleep(Duration::from_millis(500));
-}
-
-pub fn show_table(table: &Deck, player: usize, first: usize) {
-    clear();
-    table.show_as_table(player, first, 5);
-}
-
-pub fn dealer(dealer: usize) {
-    clear();
-    print!("The current dealer is Player ");
-    print_player!(dealer);
-    println!("\n");
+    if render::is_terminal() {
+        println!("\x1b[1J\x1b[H");
+    }
 }
 
 pub fn wait() {
     thread::sleep(Duration::from_millis(500));
 }
 
-pub fn show_last_non_empty(deck: &[Deck], first: usize, winner: usize) {
-    if let Some(last) = deck.last() {
-        println!("Played trick:");
-        last.show_as_table(0, first, winner);
+/// every reviewed card, the mistakes in bold, and the tricks they cost together
+pub fn review(decisions: &[Decision]) -> Vec<String> {
+    let style = render::style();
+
+    let mut lines = vec![style.bold("Trick  Played  Advised           Tricks lost")];
+    for decision in decisions {
+        let advisor = match decision.advisor {
+            Advisor::Model => "by the model",
+            Advisor::RuleBased => "by the rules",
+        };
+        let line = format!(
+            "{:>5}  {}      {} {advisor}  {:>6.2}",
+            decision.trick,
+            style.card(&decision.played),
+            style.card(&decision.advised),
+            decision.lost().max(0.0),
+        );
+        lines.push(match decision.is_mistake() {
+            true => style.bold(line),
+            false => style.dim(line),
+        });
     }
-}
 
-pub fn winner(player_id: usize) {
-    print!("Winner this round: ");
-    print_player!(player_id);
-    println!("\n");
+    let mistakes: Vec<&Decision> = decisions.iter().filter(|d| d.is_mistake()).collect();
+    lines.push(String::new());
+    lines.push(match mistakes.is_empty() {
+        true => "No mistakes, well played!".to_string(),
+        false => format!(
+            "{} mistake(s), which cost you about {:.1} trick(s).",
+            mistakes.len(),
+            mistakes.iter().map(|d| d.lost()).sum::<f64>()
+        ),
+    });
+    lines
 }
 
 pub fn wait_q() {
-    println!("\x1b[3mPress [Q] to continue.\x1b[0m");
+    println!("{}", render::italic("Press [Q] to continue."));
 
     let stdin = stdin();
     let mut stdout = stdout().into_raw_mode().unwrap();
@@ -89,7 +83,7 @@ pub fn wait_q() {
 }
 
 pub fn wait_any() {
-    println!("\x1b[3mPress [any key] to continue.\x1b[0m");
+    println!("{}", render::italic("Press [any key] to continue."));
 
     let stdin = stdin();
     let mut stdout = stdout().into_raw_mode().unwrap();
@@ -99,13 +93,14 @@ pub fn wait_any() {
     stdout.flush().unwrap();
 
     // just wait for one key to be pressed
+    #[allow(clippy::needless_ifs)]
     if stdin.keys().next().is_some() {}
 
     write!(stdout, "{}", termion::cursor::Show).unwrap();
 }
 
 pub fn wait_enter() {
-    println!("\x1b[3mPress [enter key] to continue.\x1b[0m");
+    println!("{}", render::italic("Press [enter key] to continue."));
 
     let stdin = stdin();
     let mut stdout = stdout().into_raw_mode().unwrap();
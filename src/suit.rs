@@ -2,9 +2,10 @@
  * This module describes the four suits of cards: Spades, Clubs, Diamonds and Hearts.
  */
 
+use crate::render;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::slice::Iter;
-use serde::{Serialize, Deserialize};
 
 /// The four suits a card can have. Ordered according to value.
 #[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Hash, Debug, Serialize, Deserialize)]
@@ -16,15 +17,9 @@ pub enum Suit {
 }
 
 impl fmt::Display for Suit {
+    /// the suit in the current style, see `render`
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let clr = match self {
-            Suit::Spades => "\x1b[30;47m♠\x1b[0m",
-            Suit::Clubs => "\x1b[30;47m♣\x1b[0m",
-            Suit::Diamonds => "\x1b[91;47m♦\x1b[0m",
-            Suit::Hearts => "\x1b[91;47m♥\x1b[0m",
-        };
-
-        write!(f, "{}", clr)
+        write!(f, "{}", render::style().suit(*self))
     }
 }
 
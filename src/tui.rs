@@ -0,0 +1,491 @@
+/*!
+ * Full-screen terminal interface, to play a game in.
+ *
+ * The game is drawn on the alternate screen, in raw mode, in fixed regions:
+ *
+ * ```text
+ * ╭╴Table╶──────────────────╮╭╴Scores╶──╮
+ * ╰─────────────────────────╯│          │
+ * ╭╴Last trick╶─────────────╮╰──────────╯
+ * ╰─────────────────────────╯╭╴Auction╶─╮
+ * ╭╴Your hand╶──────────────╮│          │
+ * │                         ││          │
+ * ╰─────────────────────────╯╰──────────╯
+ * ╭╴Messages╶───────────────────────────╮
+ * ╰─────────────────────────────────────╯
+ * ```
+ *
+ * The game fills in the regions of the `Screen` and calls `draw` when it changes (see `Ui`).
+ * Keys are read on a thread of their own, so the screen is drawn again
+ * when the terminal is resized while waiting for one.
+ * That thread only reads a key when one is asked for, so once the `Tui` is dropped
+ * the keys are left to whoever reads stdin next.
+ * The terminal is restored when the `Tui` is dropped, and when the game panics.
+ */
+
+use std::collections::VecDeque;
+use std::io::{self, stdin, stdout, Stdout, Write};
+use std::panic;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, Once, OnceLock, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::{AlternateScreen, IntoAlternateScreen, ToMainScreen};
+use termion::{clear, cursor, terminal_size};
+
+use crate::render::{self, Style};
+use crate::ui::Ui;
+
+/// the width of the table, last trick and hand
+const LEFT_WIDTH: u16 = 54;
+/// the narrowest the scores and auction can be
+const RIGHT_WIDTH: u16 = 26;
+/// the height of the table, last trick and hand together
+const TOP_HEIGHT: u16 = 15;
+/// the lowest the messages can be
+const MESSAGES_HEIGHT: u16 = 5;
+
+/// the smallest terminal the game fits on
+pub const MIN_SIZE: (u16, u16) = (LEFT_WIDTH + RIGHT_WIDTH, TOP_HEIGHT + MESSAGES_HEIGHT);
+
+/// how many messages are kept, the last ones are shown
+const MAX_MESSAGES: usize = 100;
+
+/// how often the terminal size is checked, while waiting
+const POLL: Duration = Duration::from_millis(50);
+
+type Terminal = AlternateScreen<RawTerminal<Stdout>>;
+
+/// the terminal while the interface is running, taken out to restore it
+static TERMINAL: Mutex<Option<Terminal>> = Mutex::new(None);
+
+/// What is drawn in every region, one string per line.
+/// Lines may contain escape codes, they are cut off at the edge of their region.
+#[derive(Clone, Default)]
+pub struct Screen {
+    pub table: Vec<String>,
+    pub last_trick: Vec<String>,
+    pub hand: Vec<String>,
+    pub scores: Vec<String>,
+    pub auction: Vec<String>,
+}
+
+impl Screen {
+    /// every region with its title
+    pub fn regions(&self) -> [(&'static str, &[String]); 5] {
+        [
+            ("Table", &self.table),
+            ("Last trick", &self.last_trick),
+            ("Your hand", &self.hand),
+            ("Scores", &self.scores),
+            ("Auction", &self.auction),
+        ]
+    }
+}
+
+/// a part of the terminal, `x` and `y` start from 0
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+/// where every region of the `Screen` goes, see the module documentation
+#[derive(Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub table: Rect,
+    pub last_trick: Rect,
+    pub hand: Rect,
+    pub scores: Rect,
+    pub auction: Rect,
+    pub messages: Rect,
+}
+
+impl Layout {
+    /// the layout for a terminal of `width` by `height`, `None` when it is too small
+    pub fn new(width: u16, height: u16) -> Option<Layout> {
+        if width < MIN_SIZE.0 || height < MIN_SIZE.1 {
+            return None;
+        }
+
+        let left = |y, height| Rect {
+            x: 0,
+            y,
+            width: LEFT_WIDTH,
+            height,
+        };
+        let right = |y, height| Rect {
+            x: LEFT_WIDTH,
+            y,
+            width: width - LEFT_WIDTH,
+            height,
+        };
+
+        Some(Layout {
+            table: left(0, 4),
+            last_trick: left(4, 4),
+            hand: left(8, TOP_HEIGHT - 8),
+            scores: right(0, 7),
+            auction: right(7, TOP_HEIGHT - 7),
+            messages: Rect {
+                x: 0,
+                y: TOP_HEIGHT,
+                width,
+                height: height - TOP_HEIGHT,
+            },
+        })
+    }
+}
+
+/// The text of `line` which fits in `width` columns, padded with spaces.
+/// Escape codes take no room, the colours are reset when they are used.
+pub fn fit(line: &str, width: usize) -> String {
+    let mut fitted = String::with_capacity(line.len() + width);
+    let mut used = 0;
+    let mut escaped = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // copy the escape code up to and including its final letter
+            escaped = true;
+            fitted.push(c);
+            for c in chars.by_ref() {
+                fitted.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else if used < width {
+            fitted.push(c);
+            used += 1;
+        }
+    }
+
+    if escaped {
+        fitted.push_str(render::RESET);
+    }
+    fitted.push_str(&" ".repeat(width - used));
+    fitted
+}
+
+/// the thread which reads a key from stdin when asked, `None` when stdin is closed
+struct KeyReader {
+    asks: Sender<()>,
+    keys: Receiver<Option<Key>>,
+}
+
+/// the key reader, started once for the whole program
+fn keys() -> &'static Mutex<KeyReader> {
+    static KEYS: OnceLock<Mutex<KeyReader>> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        let (asks, asked) = mpsc::channel();
+        let (sender, keys) = mpsc::channel();
+        thread::spawn(move || {
+            for () in asked {
+                let key = stdin().keys().next().and_then(Result::ok);
+                if sender.send(key).is_err() {
+                    break;
+                }
+            }
+        });
+        Mutex::new(KeyReader { asks, keys })
+    })
+}
+
+/// leave the alternate screen and raw mode, when the interface is running
+fn restore() {
+    let mut terminal = match TERMINAL.try_lock() {
+        Ok(terminal) => terminal,
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        // panicked while drawing, raw mode ends when the terminal is dropped while unwinding
+        Err(TryLockError::WouldBlock) => {
+            print!("{}{}", ToMainScreen, cursor::Show);
+            let _ = stdout().flush();
+            return;
+        }
+    };
+
+    if let Some(mut terminal) = terminal.take() {
+        let _ = write!(terminal, "{}", cursor::Show);
+        let _ = terminal.flush();
+    }
+}
+
+/// a title and some lines on the whole screen, scrolled down to `top`
+struct Page {
+    title: String,
+    lines: Vec<String>,
+    top: usize,
+}
+
+pub struct Tui {
+    pub screen: Screen,
+    messages: VecDeque<String>,
+    /// the text shown instead of the screen, until a key is pressed
+    page: Option<Page>,
+    size: (u16, u16),
+}
+
+impl Tui {
+    /// Switch to the alternate screen in raw mode, until the `Tui` is dropped.
+    ///
+    /// Fails when stdout is not a terminal.
+    pub fn start() -> io::Result<Tui> {
+        let mut terminal = stdout().into_raw_mode()?.into_alternate_screen()?;
+        write!(terminal, "{}", cursor::Hide)?;
+        *TERMINAL.lock().expect("the terminal is not poisoned") = Some(terminal);
+
+        // restore the terminal before the panic message is printed
+        static HOOK: Once = Once::new();
+        HOOK.call_once(|| {
+            let hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                restore();
+                hook(info);
+            }));
+        });
+
+        let mut tui = Tui {
+            screen: Screen::default(),
+            messages: VecDeque::new(),
+            page: None,
+            size: terminal_size()?,
+        };
+        tui.draw();
+        Ok(tui)
+    }
+
+    fn redraw_resized(&mut self) {
+        if let Ok(size) = terminal_size() {
+            if size != self.size {
+                self.size = size;
+                self.draw();
+            }
+        }
+    }
+
+    fn draw_on(&self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = self.size;
+        write!(out, "{}", clear::All)?;
+
+        if let Some(page) = &self.page {
+            let rect = Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            };
+            let room = page_room(height);
+            let top = page.top.min(page.lines.len().saturating_sub(room));
+            let mut lines: Vec<String> = page.lines.iter().skip(top).take(room).cloned().collect();
+            lines.push(String::new());
+            lines.push(render::italic(match page.lines.len() > room {
+                true => "Scroll with the [arrow keys], press any other key to continue.",
+                false => "Press any key to continue.",
+            }));
+            draw_region(out, rect, &page.title, &lines)?;
+            return out.flush();
+        }
+
+        let layout = match Layout::new(width, height) {
+            Some(layout) => layout,
+            None => {
+                let (min_width, min_height) = MIN_SIZE;
+                write!(
+                    out,
+                    "{}Make the terminal at least {min_width}x{min_height} to play.",
+                    cursor::Goto(1, 1)
+                )?;
+                return out.flush();
+            }
+        };
+
+        let screen = &self.screen;
+        draw_region(out, layout.table, "Table", &screen.table)?;
+        draw_region(out, layout.last_trick, "Last trick", &screen.last_trick)?;
+        draw_region(out, layout.hand, "Your hand", &screen.hand)?;
+        draw_region(out, layout.scores, "Scores", &screen.scores)?;
+        draw_region(out, layout.auction, "Auction", &screen.auction)?;
+
+        // the last messages which fit
+        let shown = (layout.messages.height as usize).saturating_sub(2);
+        let messages = self
+            .messages
+            .iter()
+            .skip(self.messages.len().saturating_sub(shown))
+            .cloned()
+            .collect::<Vec<_>>();
+        draw_region(out, layout.messages, "Messages", &messages)?;
+
+        out.flush()
+    }
+}
+
+impl Ui for Tui {
+    fn screen(&mut self) -> &mut Screen {
+        &mut self.screen
+    }
+
+    /// add a line to the messages, and draw the screen again
+    fn say(&mut self, message: &str) {
+        for line in message.lines() {
+            self.messages.push_back(line.to_string());
+        }
+        while self.messages.len() > MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+        self.draw();
+    }
+
+    /// wait for the next key, drawing the screen again when the terminal is resized
+    fn key(&mut self) -> Key {
+        let reader = keys().lock().expect("keys are not poisoned");
+        if reader.asks.send(()).is_err() {
+            return Key::Ctrl('c');
+        }
+        loop {
+            match reader.keys.recv_timeout(POLL) {
+                Ok(Some(key)) => return key,
+                Err(RecvTimeoutError::Timeout) => self.redraw_resized(),
+                // stdin is closed, like when quitting
+                Ok(None) | Err(RecvTimeoutError::Disconnected) => return Key::Ctrl('c'),
+            }
+        }
+    }
+
+    fn pause(&mut self, millis: u64) {
+        let until = Instant::now() + Duration::from_millis(millis);
+        while Instant::now() < until {
+            thread::sleep(POLL.min(until - Instant::now()));
+            self.redraw_resized();
+        }
+    }
+
+    /// show `lines` on the whole screen until a key is pressed, the arrow keys scroll
+    fn page(&mut self, title: &str, lines: Vec<String>) {
+        self.page = Some(Page {
+            title: title.to_string(),
+            lines,
+            top: 0,
+        });
+
+        loop {
+            self.draw();
+            let key = self.key();
+            let room = page_room(self.size.1);
+            let page = self.page.as_mut().expect("the page is shown");
+            let bottom = page.lines.len().saturating_sub(room);
+            match key {
+                Key::Char('j') | Key::Down => page.top = (page.top + 1).min(bottom),
+                Key::Char('k') | Key::Up => page.top = page.top.saturating_sub(1),
+                _ => break,
+            }
+        }
+
+        self.page = None;
+        self.draw();
+    }
+
+    /// draw everything again, in the current size of the terminal
+    fn draw(&mut self) {
+        let mut terminal = TERMINAL.lock().expect("the terminal is not poisoned");
+        if let Some(terminal) = terminal.as_mut() {
+            self.draw_on(terminal)
+                .expect("the terminal should be writable");
+        }
+    }
+}
+
+/// the lines of a page which fit in `height`, below them an empty line and how to continue
+fn page_room(height: u16) -> usize {
+    (height as usize).saturating_sub(4)
+}
+
+/// a box with a title at the top, around as many lines as fit
+fn draw_region(out: &mut impl Write, rect: Rect, title: &str, lines: &[String]) -> io::Result<()> {
+    let style: Style = render::style();
+    let frame = style.frame();
+    let inner = rect.width.saturating_sub(2) as usize;
+    let title: String = format!("{}{title}{}", frame.left_end, frame.right_end)
+        .chars()
+        .take(inner)
+        .collect();
+    let rest = inner - title.chars().count();
+
+    let goto = |row: u16| cursor::Goto(rect.x + 1, rect.y + row + 1);
+
+    write!(
+        out,
+        "{}{}{}{}",
+        goto(0),
+        style.dim(frame.top_left),
+        style.bold(title),
+        style.dim(format!("{}{}", style.line(rest), frame.top_right))
+    )?;
+
+    for row in 1..rect.height.saturating_sub(1) {
+        let line = lines.get(row as usize - 1).map_or("", |line| line.as_str());
+        write!(
+            out,
+            "{}{}{}{}",
+            goto(row),
+            style.dim(frame.vertical),
+            fit(line, inner),
+            style.dim(frame.vertical)
+        )?;
+    }
+
+    write!(
+        out,
+        "{}{}",
+        goto(rect.height.saturating_sub(1)),
+        style.dim(format!(
+            "{}{}{}",
+            frame.bottom_left,
+            style.line(inner),
+            frame.bottom_right
+        ))
+    )
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_fit_their_region() {
+        assert_eq!(fit("abc", 5), "abc  ");
+        assert_eq!(fit("abcdef", 4), "abcd");
+        assert_eq!(
+            fit("\x1b[1mbold\x1b[0m text", 6),
+            format!("\x1b[1mbold\x1b[0m t{}", render::RESET)
+        );
+        assert_eq!(fit("T♥", 3), "T♥ ");
+    }
+
+    #[test]
+    fn regions_fill_the_terminal() {
+        assert_eq!(Layout::new(MIN_SIZE.0 - 1, 40), None);
+        assert_eq!(Layout::new(120, MIN_SIZE.1 - 1), None);
+
+        let layout = Layout::new(100, 30).unwrap();
+        assert_eq!(layout.scores.x + layout.scores.width, 100);
+        assert_eq!(layout.messages.y + layout.messages.height, 30);
+        assert_eq!(layout.hand.y + layout.hand.height, layout.messages.y);
+        assert_eq!(layout.auction.y + layout.auction.height, layout.messages.y);
+        assert_eq!(layout.table.width, LEFT_WIDTH);
+    }
+}
@@ -0,0 +1,130 @@
+/*!
+ * What a game is played on: the full-screen `Tui` in a terminal,
+ * or `Plain` lines of text when the output is piped.
+ *
+ * The game fills in the regions of the `Screen` and calls `draw` when it changes,
+ * messages are said and keys are asked one at a time.
+ */
+
+use std::collections::VecDeque;
+use std::io::{stdin, stdout, Write};
+
+use termion::event::Key;
+
+use crate::tui::Screen;
+
+pub trait Ui {
+    /// the regions to fill in, shown on the next `draw`
+    fn screen(&mut self) -> &mut Screen;
+
+    /// show the screen as it is filled in now
+    fn draw(&mut self);
+
+    /// add a line to the messages
+    fn say(&mut self, message: &str);
+
+    /// wait for the next key, `ctrl-c` when there are no keys left to read
+    fn key(&mut self) -> Key;
+
+    /// wait a bit, so the players can follow what happens
+    fn pause(&mut self, millis: u64);
+
+    /// show `lines` with a title, instead of the screen, until a key is pressed
+    fn page(&mut self, title: &str, lines: Vec<String>);
+
+    /// ask a yes or no question in the messages, `default` is the answer on [enter]
+    fn ask(&mut self, question: &str, default: bool) -> bool {
+        let options = match default {
+            true => "[Y/n]",
+            false => "[y/N]",
+        };
+        self.say(&format!("{question} {options}"));
+
+        loop {
+            match self.key() {
+                Key::Char('y') | Key::Char('Y') => return true,
+                Key::Char('n') | Key::Char('N') | Key::Ctrl('c') => return false,
+                Key::Char('\n') => return default,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The game as lines of text, for when stdout is not a terminal.
+///
+/// A region is printed when it changed since the last `draw`.
+/// Keys are read a line at a time: every character is a key, an empty line is [enter].
+#[derive(Default)]
+pub struct Plain {
+    pub screen: Screen,
+    /// the screen as it was printed last
+    printed: Screen,
+    /// the keys of the last line that are not asked for yet
+    keys: VecDeque<Key>,
+}
+
+impl Plain {
+    pub fn new() -> Plain {
+        Plain::default()
+    }
+}
+
+impl Ui for Plain {
+    fn screen(&mut self) -> &mut Screen {
+        &mut self.screen
+    }
+
+    fn draw(&mut self) {
+        for ((title, lines), (_, printed)) in self
+            .screen
+            .regions()
+            .into_iter()
+            .zip(self.printed.regions())
+        {
+            if lines != printed {
+                println!("{title}:");
+                for line in lines {
+                    println!("  {line}");
+                }
+            }
+        }
+        self.printed = self.screen.clone();
+        let _ = stdout().flush();
+    }
+
+    fn say(&mut self, message: &str) {
+        println!("{message}");
+    }
+
+    fn key(&mut self) -> Key {
+        if self.keys.is_empty() {
+            let _ = stdout().flush();
+            let mut line = String::new();
+            match stdin().read_line(&mut line) {
+                Ok(0) | Err(_) => return Key::Ctrl('c'),
+                Ok(_) => {}
+            }
+
+            self.keys = line.trim_end().chars().map(Key::Char).collect();
+            if self.keys.is_empty() {
+                self.keys.push_back(Key::Char('\n'));
+            }
+        }
+
+        self.keys.pop_front().expect("a key was read")
+    }
+
+    fn pause(&mut self, _millis: u64) {
+        // nobody watches the lines being printed
+        let _ = stdout().flush();
+    }
+
+    fn page(&mut self, title: &str, lines: Vec<String>) {
+        println!("{title}:");
+        for line in lines {
+            println!("  {line}");
+        }
+        println!();
+    }
+}